@@ -0,0 +1,51 @@
+//! Micro-benchmark: uncached selector/class lookups vs per-call-site cached `sel!`/`class!`.
+//!
+//! Run with `cargo bench --bench selector_cache` on macOS.
+
+use objc::runtime::{Class, Sel};
+use objc::{class, sel, sel_impl};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn measure(label: &str, mut f: impl FnMut()) -> Duration {
+    // warm up (also populates the call-site caches)
+    for _ in 0..1000 {
+        f();
+    }
+
+    let t = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = t.elapsed();
+    println!(
+        "{label:<40} {:>10.2} ns/iter",
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+
+    elapsed
+}
+
+fn main() {
+    let uncached_sel = measure("Sel::register(\"convertSizeToBacking:\")", || {
+        black_box(Sel::register(black_box("convertSizeToBacking:")));
+    });
+    let cached_sel = measure("sel!(convertSizeToBacking:)", || {
+        black_box(sel!(convertSizeToBacking:));
+    });
+    let uncached_class = measure("Class::get(\"NSView\")", || {
+        black_box(Class::get(black_box("NSView")));
+    });
+    let cached_class = measure("class!(NSView)", || {
+        black_box(class!(NSView));
+    });
+
+    println!();
+    println!(
+        "selector speedup: {:.1}x, class speedup: {:.1}x",
+        uncached_sel.as_secs_f64() / cached_sel.as_secs_f64(),
+        uncached_class.as_secs_f64() / cached_class.as_secs_f64()
+    );
+}
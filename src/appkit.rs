@@ -2,7 +2,7 @@
 
 use crate::{
     CALayer, CGColor, CGColorRef, CGFloat, CGPoint, CGRect, CGSize, CocoaMutableObject, CocoaObject, NSInteger,
-    NSObject, NSString, NSUInteger,
+    NSObject, NSString, NSTimeInterval, NSUInteger,
 };
 use bitflags::bitflags;
 use objc::{class, msg_send, runtime::*, sel, sel_impl};
@@ -11,15 +11,18 @@ use std::mem::zeroed;
 
 type NSRunLoopMode = *mut Object;
 
-#[link(name = "AppKit", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "AppKit", kind = "framework"))]
 unsafe extern "system" {
     #[allow(improper_ctypes)]
     pub unsafe static NSFontAttributeName: *mut NSString;
+    pub unsafe static NSEventTrackingRunLoopMode: NSRunLoopMode;
+    pub unsafe static NSModalPanelRunLoopMode: NSRunLoopMode;
 }
 
-#[link(name = "Foundation", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "Foundation", kind = "framework"))]
 unsafe extern "system" {
     pub unsafe static NSDefaultRunLoopMode: NSRunLoopMode;
+    pub unsafe static NSRunLoopCommonModes: NSRunLoopMode;
 }
 
 pub type NSSize = CGSize;
@@ -563,7 +566,6 @@ objc_ext::DefineObjcObjectWrapper!(pub NSResponder : NSObject);
 pub enum NSEventType {
     ApplicationDefined = 15,
 }
-pub type NSTimeInterval = core::ffi::c_double;
 objc_ext::DefineObjcObjectWrapper!(pub NSEvent : NSObject);
 impl NSEvent {
     pub fn new_other_event(
@@ -130,7 +130,7 @@ pub struct AudioStreamBasicDescription {
     pub _reserved: u32,
 }
 
-#[link(name = "AudioUnit", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "AudioUnit", kind = "framework"))]
 extern "system" {
     pub fn AudioComponentFindNext(
         in_component: AudioComponent,
@@ -1,12 +1,28 @@
 //! Core Foundation
+#![allow(non_upper_case_globals)]
 
-use crate::{NSArray, NSAttributedString, NSDictionary, NSNumber, NSString};
+use crate::UniChar;
+#[cfg(feature = "std")]
+use crate::{NSArray, NSAttributedString, NSData, NSDate, NSDictionary, NSMutableData, NSNumber, NSString, NSURL};
+use alloc::alloc::{GlobalAlloc, Layout};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo};
+use core::sync::atomic::{AtomicBool, Ordering};
 use libc::*;
+#[cfg(feature = "std")]
 use objc_ext::ObjcObject;
-use std::ops::{Range, RangeFrom, RangeFull, RangeTo};
 
 /// An untyped "generic" reference to any Core Foundation object.
 pub type CFTypeRef = *const c_void;
+/// A type for unique, constant integer values that identify particular Core Foundation opaque types.
+pub type CFTypeID = c_ulong;
 
 /// Retains a Core Foundation object.
 pub unsafe extern "system" fn cfretain<T>(cf: *mut T) -> *mut T {
@@ -30,6 +46,54 @@ pub unsafe trait CoreObject {
     }
 }
 
+/// CoreFoundation object types that have a runtime type identifier.
+pub trait CFTypeInfo: CoreObject + Sized {
+    /// Returns the type identifier of the type.
+    fn type_id() -> CFTypeID;
+
+    /// Upcasts to the generic CoreFoundation object type.
+    #[inline(always)]
+    fn as_cftype(&self) -> &CFType {
+        unsafe { &*(self as *const Self as *const CFType) }
+    }
+}
+
+DefineCoreObject! {
+    /// The base type of all CoreFoundation objects.
+    pub CFType;
+}
+impl CFType {
+    /// Returns the unique identifier of the runtime type of the object.
+    #[inline(always)]
+    pub fn type_id(&self) -> CFTypeID {
+        unsafe { CFGetTypeID(self as *const _ as _) }
+    }
+
+    /// Returns true if the runtime type of the object is `T`.
+    #[inline(always)]
+    pub fn is<T: CFTypeInfo>(&self) -> bool {
+        self.type_id() == T::type_id()
+    }
+
+    /// Returns the object as a reference to `T`, if the runtime type of the object is `T`.
+    #[inline]
+    pub fn downcast<T: CFTypeInfo>(&self) -> Option<&T> {
+        if self.is::<T>() {
+            Some(unsafe { &*(self as *const Self as *const T) })
+        } else {
+            None
+        }
+    }
+}
+impl core::fmt::Debug for CFType {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match unsafe { CoreRetainedObject::retained(CFCopyDescription(self as *const _ as _)) } {
+            Some(d) => core::fmt::Display::fmt(&*d, f),
+            None => write!(f, "<CFType {:p}>", self),
+        }
+    }
+}
+
 /// autorelease box for CoreFoundation objects
 #[repr(transparent)]
 pub struct CoreRetainedObject<T: CoreObject>(*const T);
@@ -50,6 +114,36 @@ impl<T: CoreObject> CoreRetainedObject<T> {
     pub const fn as_ptr(&self) -> *const T {
         self.0
     }
+
+    /// Retains the object.
+    #[inline(always)]
+    pub fn retain(obj: &T) -> Self {
+        unsafe {
+            T::retain(obj);
+        }
+
+        Self(obj as *const T)
+    }
+
+    /// Returns the object as a reference to `U`, if the runtime type of the object is `U`.
+    #[inline(always)]
+    pub fn downcast<U: CFTypeInfo>(&self) -> Option<&U> {
+        unsafe { &*(self.0 as *const CFType) }.downcast()
+    }
+
+    /// Converts into a retained object of `U`, if the runtime type of the object is `U`.
+    #[inline]
+    pub fn downcast_into<U: CFTypeInfo>(self) -> Result<CoreRetainedObject<U>, Self> {
+        if unsafe { &*(self.0 as *const CFType) }.is::<U>() {
+            let p = self.0 as *const U;
+            // ownership moves to the new box
+            core::mem::forget(self);
+
+            Ok(unsafe { CoreRetainedObject::retained_unchecked(p) })
+        } else {
+            Err(self)
+        }
+    }
 }
 impl<T: CoreObject> Clone for CoreRetainedObject<T> {
     #[inline(always)]
@@ -90,6 +184,18 @@ impl<T: CoreObject> core::borrow::Borrow<T> for CoreRetainedObject<T> {
     }
 }
 
+impl<T: CFTypeInfo> CoreRetainedObject<T> {
+    /// Converts into a retained generic object.
+    #[inline(always)]
+    pub fn into_cftype(self) -> CoreRetainedObject<CFType> {
+        let p = self.0 as *const CFType;
+        // ownership moves to the new box
+        core::mem::forget(self);
+
+        CoreRetainedObject(p)
+    }
+}
+
 /// autorelease smart pointer for mutable CoreFoundation objects
 #[repr(transparent)]
 pub struct CoreRetainedMutableObject<T: CoreObject>(core::ptr::NonNull<T>);
@@ -172,6 +278,7 @@ DefineCoreObject! {
 }
 /// A reference to an immutable array object.
 pub type CFArrayRef = *const CFArray;
+DeclareCFTypeID!(CFArray = CFArrayGetTypeID);
 impl CFArray {
     /// Returns the number of values currently in an array.
     #[inline(always)]
@@ -179,18 +286,32 @@ impl CFArray {
         unsafe { CFArrayGetCount(self as *const _ as _) }
     }
 
+    /// Returns true if the array contains no values.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     /// Retrieves a value at a given index.
     #[inline(always)]
     pub unsafe fn get<T>(&self, idx: CFIndex) -> Option<&T> {
         (CFArrayGetValueAtIndex(self as *const _ as _, idx) as *const T).as_ref()
     }
 
+    /// Views the array as an array of the specified element type, without checking types of its values.
+    #[inline(always)]
+    pub const unsafe fn as_typed_unchecked<T: CoreObject>(&self) -> &CFArrayOf<T> {
+        core::mem::transmute(self)
+    }
+
     /// toll-free bridging but no type-safety provided
+    #[cfg(feature = "std")]
     pub const unsafe fn as_nsarray_ref_unchecked<T: ObjcObject>(&self) -> &NSArray<T> {
         core::mem::transmute(self)
     }
 }
 /// toll-free bridging
+#[cfg(feature = "std")]
 impl<T: ObjcObject> AsRef<CFArray> for NSArray<T> {
     #[inline(always)]
     fn as_ref(&self) -> &CFArray {
@@ -198,13 +319,208 @@ impl<T: ObjcObject> AsRef<CFArray> for NSArray<T> {
     }
 }
 
+/// A CFArray whose values are all of the type `T`.
+#[repr(transparent)]
+pub struct CFArrayOf<T: CoreObject>(CFArray, PhantomData<*const T>);
+unsafe impl<T: CoreObject> CoreObject for CFArrayOf<T> {}
+impl<T: CoreObject> core::ops::Deref for CFArrayOf<T> {
+    type Target = CFArray;
+
+    #[inline(always)]
+    fn deref(&self) -> &CFArray {
+        &self.0
+    }
+}
+impl<T: CoreObject> CFArrayOf<T> {
+    /// Creates an immutable array containing the given values, retaining each of them.
+    #[inline]
+    pub fn new(values: &[&T]) -> Option<CoreRetainedObject<Self>> {
+        unsafe {
+            CoreRetainedObject::retained(CFArrayCreate(
+                core::ptr::null_mut(),
+                values.as_ptr() as _,
+                values.len() as _,
+                &kCFTypeArrayCallBacks,
+            ) as *const Self)
+        }
+    }
+
+    /// Retrieves a value at a given index, or `None` if the index is out of bounds.
+    #[inline]
+    pub fn get(&self, idx: CFIndex) -> Option<&T> {
+        if idx < 0 || idx >= self.len() {
+            return None;
+        }
+
+        unsafe { self.0.get(idx) }
+    }
+
+    /// Returns an iterator over the values.
+    #[inline(always)]
+    pub fn iter(&self) -> CFArrayIter<'_, T> {
+        CFArrayIter {
+            array: self,
+            front: 0,
+            back: self.len(),
+        }
+    }
+}
+impl<'a, T: CoreObject> IntoIterator for &'a CFArrayOf<T> {
+    type Item = &'a T;
+    type IntoIter = CFArrayIter<'a, T>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of a `CFArrayOf`.
+pub struct CFArrayIter<'a, T: CoreObject> {
+    array: &'a CFArrayOf<T>,
+    front: CFIndex,
+    back: CFIndex,
+}
+impl<'a, T: CoreObject> Iterator for CFArrayIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        let v = unsafe { self.array.0.get(self.front) };
+        self.front += 1;
+        v
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.back - self.front) as usize;
+        (n, Some(n))
+    }
+}
+impl<'a, T: CoreObject> DoubleEndedIterator for CFArrayIter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        unsafe { self.array.0.get(self.back) }
+    }
+}
+impl<'a, T: CoreObject> ExactSizeIterator for CFArrayIter<'a, T> {}
+
+impl<'a, T: CoreObject> FromIterator<&'a T> for CoreRetainedObject<CFArrayOf<T>> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        let values = iter.into_iter().collect::<Vec<_>>();
+
+        CFArrayOf::new(&values).expect("CFArrayCreate failed")
+    }
+}
+
+/// A mutable CFArray whose values are all of the type `T`.
+#[repr(transparent)]
+pub struct CFMutableArray<T: CoreObject>(CFArrayOf<T>);
+/// A reference to a mutable array object.
+pub type CFMutableArrayRef = *mut CFArray;
+unsafe impl<T: CoreObject> CoreObject for CFMutableArray<T> {}
+impl<T: CoreObject> core::ops::Deref for CFMutableArray<T> {
+    type Target = CFArrayOf<T>;
+
+    #[inline(always)]
+    fn deref(&self) -> &CFArrayOf<T> {
+        &self.0
+    }
+}
+impl<T: CoreObject> CFMutableArray<T> {
+    /// Creates an empty mutable array.
+    #[inline(always)]
+    pub fn new() -> Option<CoreRetainedMutableObject<Self>> {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty mutable array that holds at most `cap` values (0 means unlimited).
+    #[inline(always)]
+    pub fn with_capacity(cap: CFIndex) -> Option<CoreRetainedMutableObject<Self>> {
+        unsafe {
+            CoreRetainedMutableObject::from_retained_ptr(CFArrayCreateMutable(
+                core::ptr::null_mut(),
+                cap,
+                &kCFTypeArrayCallBacks,
+            ) as *mut Self)
+        }
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&mut self) -> CFMutableArrayRef {
+        self as *mut Self as _
+    }
+
+    /// Adds a value to the end of the array.
+    #[inline(always)]
+    pub fn push(&mut self, value: &T) {
+        unsafe { CFArrayAppendValue(self.as_mut_ptr(), value as *const T as _) }
+    }
+
+    /// Inserts a value at a given index. Values at or after the index are moved back.
+    #[inline]
+    pub fn insert(&mut self, idx: CFIndex, value: &T) {
+        assert!(idx >= 0 && idx <= self.len(), "index out of bounds");
+
+        unsafe { CFArrayInsertValueAtIndex(self.as_mut_ptr(), idx, value as *const T as _) }
+    }
+
+    /// Replaces the value at a given index.
+    #[inline]
+    pub fn set(&mut self, idx: CFIndex, value: &T) {
+        assert!(idx >= 0 && idx < self.len(), "index out of bounds");
+
+        unsafe { CFArraySetValueAtIndex(self.as_mut_ptr(), idx, value as *const T as _) }
+    }
+
+    /// Removes the value at a given index. Values after the index are moved forward.
+    #[inline]
+    pub fn remove(&mut self, idx: CFIndex) {
+        assert!(idx >= 0 && idx < self.len(), "index out of bounds");
+
+        unsafe { CFArrayRemoveValueAtIndex(self.as_mut_ptr(), idx) }
+    }
+
+    /// Removes all values from the array.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        unsafe { CFArrayRemoveAllValues(self.as_mut_ptr()) }
+    }
+}
+impl<'a, T: CoreObject> Extend<&'a T> for CFMutableArray<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for v in iter {
+            self.push(v);
+        }
+    }
+}
+impl<'a, T: CoreObject> FromIterator<&'a T> for CoreRetainedMutableObject<CFMutableArray<T>> {
+    fn from_iter<I: IntoIterator<Item = &'a T>>(iter: I) -> Self {
+        let mut a = CFMutableArray::new().expect("CFArrayCreateMutable failed");
+        a.extend(iter);
+
+        a
+    }
+}
+
 DefineCoreObject! {
     /// Manages associations of key-value pairs.
     pub CFDictionary;
 }
 /// A reference to an immutable dictionary object.
 pub type CFDictionaryRef = *const CFDictionary;
+DeclareCFTypeID!(CFDictionary = CFDictionaryGetTypeID);
 /// toll-free bridging
+#[cfg(feature = "std")]
 impl<K: ObjcObject, V: ObjcObject> AsRef<CFDictionary> for NSDictionary<K, V> {
     #[inline(always)]
     fn as_ref(&self) -> &CFDictionary {
@@ -218,33 +534,270 @@ impl CFDictionary {
         (CFDictionaryGetValue(self as _, key as *const K as _) as *const T).as_ref()
     }
 
+    /// Returns the number of key-value pairs in a dictionary.
+    #[inline(always)]
+    pub fn len(&self) -> CFIndex {
+        unsafe { CFDictionaryGetCount(self) }
+    }
+
+    /// Returns true if the dictionary contains no key-value pairs.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Views the dictionary as a dictionary of the specified key and value types, without checking types of its
+    /// contents.
+    #[inline(always)]
+    pub const unsafe fn as_typed_unchecked<K: CoreObject, V: CoreObject>(&self) -> &CFDictionaryOf<K, V> {
+        core::mem::transmute(self)
+    }
+
     /// toll-free bridging but no type-safety provided
+    #[cfg(feature = "std")]
     pub const unsafe fn as_nsdictionary_ref_unchecked<K: ObjcObject, V: ObjcObject>(&self) -> &NSDictionary<K, V> {
         core::mem::transmute(self)
     }
 }
 
+/// A CFDictionary whose keys are all of the type `K` and values are all of the type `V`.
+#[repr(transparent)]
+pub struct CFDictionaryOf<K: CoreObject, V: CoreObject>(CFDictionary, PhantomData<(*const K, *const V)>);
+unsafe impl<K: CoreObject, V: CoreObject> CoreObject for CFDictionaryOf<K, V> {}
+impl<K: CoreObject, V: CoreObject> core::ops::Deref for CFDictionaryOf<K, V> {
+    type Target = CFDictionary;
+
+    #[inline(always)]
+    fn deref(&self) -> &CFDictionary {
+        &self.0
+    }
+}
+impl<K: CoreObject, V: CoreObject> CFDictionaryOf<K, V> {
+    /// Creates an immutable dictionary containing the given key-value pairs, retaining each of them.
+    pub fn new(keys: &[&K], values: &[&V]) -> Option<CoreRetainedObject<Self>> {
+        assert_eq!(keys.len(), values.len(), "mismatching count of keys and values");
+
+        unsafe {
+            CoreRetainedObject::retained(CFDictionaryCreate(
+                core::ptr::null_mut(),
+                keys.as_ptr() as _,
+                values.as_ptr() as _,
+                keys.len() as _,
+                &kCFTypeDictionaryKeyCallBacks,
+                &kCFTypeDictionaryValueCallBacks,
+            ) as *const Self)
+        }
+    }
+
+    /// Returns the value associated with a given key.
+    #[inline(always)]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        unsafe { self.0.get(key) }
+    }
+
+    /// Returns a boolean value that indicates whether a given key is in a dictionary.
+    #[inline(always)]
+    pub fn contains_key(&self, key: &K) -> bool {
+        unsafe { CFDictionaryContainsKey(&self.0, key as *const K as _) }
+    }
+
+    /// Returns all the keys and values in the dictionary, in the same order.
+    pub fn keys_and_values(&self) -> (Vec<&K>, Vec<&V>) {
+        let n = self.len() as usize;
+        let mut keys = Vec::<&K>::with_capacity(n);
+        let mut values = Vec::<&V>::with_capacity(n);
+        unsafe {
+            CFDictionaryGetKeysAndValues(&self.0, keys.as_mut_ptr() as _, values.as_mut_ptr() as _);
+            keys.set_len(n);
+            values.set_len(n);
+        }
+
+        (keys, values)
+    }
+
+    /// Returns all the keys in the dictionary.
+    #[inline(always)]
+    pub fn keys(&self) -> Vec<&K> {
+        self.keys_and_values().0
+    }
+
+    /// Returns all the values in the dictionary.
+    #[inline(always)]
+    pub fn values(&self) -> Vec<&V> {
+        self.keys_and_values().1
+    }
+
+    /// Returns an iterator over the key-value pairs.
+    ///
+    /// The pairs are snapshotted with `CFDictionaryGetKeysAndValues` when this is called.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        let (keys, values) = self.keys_and_values();
+
+        keys.into_iter().zip(values)
+    }
+}
+impl<'a, K: CoreObject, V: CoreObject> FromIterator<(&'a K, &'a V)> for CoreRetainedObject<CFDictionaryOf<K, V>> {
+    fn from_iter<I: IntoIterator<Item = (&'a K, &'a V)>>(iter: I) -> Self {
+        let (keys, values): (Vec<_>, Vec<_>) = iter.into_iter().unzip();
+
+        CFDictionaryOf::new(&keys, &values).expect("CFDictionaryCreate failed")
+    }
+}
+
+/// A mutable CFDictionary whose keys are all of the type `K` and values are all of the type `V`.
+#[repr(transparent)]
+pub struct CFMutableDictionary<K: CoreObject, V: CoreObject>(CFDictionaryOf<K, V>);
+/// A reference to a mutable dictionary object.
+pub type CFMutableDictionaryRef = *mut CFDictionary;
+unsafe impl<K: CoreObject, V: CoreObject> CoreObject for CFMutableDictionary<K, V> {}
+impl<K: CoreObject, V: CoreObject> core::ops::Deref for CFMutableDictionary<K, V> {
+    type Target = CFDictionaryOf<K, V>;
+
+    #[inline(always)]
+    fn deref(&self) -> &CFDictionaryOf<K, V> {
+        &self.0
+    }
+}
+impl<K: CoreObject, V: CoreObject> CFMutableDictionary<K, V> {
+    /// Creates an empty mutable dictionary.
+    #[inline(always)]
+    pub fn new() -> Option<CoreRetainedMutableObject<Self>> {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty mutable dictionary that holds at most `cap` pairs (0 means unlimited).
+    #[inline(always)]
+    pub fn with_capacity(cap: CFIndex) -> Option<CoreRetainedMutableObject<Self>> {
+        unsafe {
+            CoreRetainedMutableObject::from_retained_ptr(CFDictionaryCreateMutable(
+                core::ptr::null_mut(),
+                cap,
+                &kCFTypeDictionaryKeyCallBacks,
+                &kCFTypeDictionaryValueCallBacks,
+            ) as *mut Self)
+        }
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&mut self) -> CFMutableDictionaryRef {
+        self as *mut Self as _
+    }
+
+    /// Sets the value for a key, replacing the previous value if the key already exists.
+    #[inline(always)]
+    pub fn insert(&mut self, key: &K, value: &V) {
+        unsafe { CFDictionarySetValue(self.as_mut_ptr(), key as *const K as _, value as *const V as _) }
+    }
+
+    /// Removes a key-value pair.
+    #[inline(always)]
+    pub fn remove(&mut self, key: &K) {
+        unsafe { CFDictionaryRemoveValue(self.as_mut_ptr(), key as *const K as _) }
+    }
+
+    /// Removes all key-value pairs from the dictionary.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        unsafe { CFDictionaryRemoveAllValues(self.as_mut_ptr()) }
+    }
+}
+impl<'a, K: CoreObject, V: CoreObject> Extend<(&'a K, &'a V)> for CFMutableDictionary<K, V> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+impl<'a, K: CoreObject, V: CoreObject> FromIterator<(&'a K, &'a V)>
+    for CoreRetainedMutableObject<CFMutableDictionary<K, V>>
+{
+    fn from_iter<I: IntoIterator<Item = (&'a K, &'a V)>>(iter: I) -> Self {
+        let mut d = CFMutableDictionary::new().expect("CFDictionaryCreateMutable failed");
+        d.extend(iter);
+
+        d
+    }
+}
+
+DefineOpaqueFFIObject! {
+    /// Callbacks used by a CFArray to operate on its values.
+    pub struct CFArrayCallBacks;
+}
+DefineOpaqueFFIObject! {
+    /// Callbacks used by a CFDictionary to operate on its keys.
+    pub struct CFDictionaryKeyCallBacks;
+}
+DefineOpaqueFFIObject! {
+    /// Callbacks used by a CFDictionary to operate on its values.
+    pub struct CFDictionaryValueCallBacks;
+}
+
 DefineCoreObject! {
     /// Manages character strings and associated sets of attributes.
     pub CFAttributedString;
 }
 /// A reference to a CFAttributedString object.
 pub type CFAttributedStringRef = *const CFAttributedString;
+DeclareCFTypeID!(CFAttributedString = CFAttributedStringGetTypeID);
+#[cfg(feature = "std")]
 TollfreeBridge!(CFAttributedString = NSAttributedString);
 
 /// A structure representing a range of sequential items in a container.
+///
+/// CoreText functions treat a length of 0 as "up to the end of the text", so `CFRange::from(..)` (`0, 0`) selects
+/// the whole string. `CFRange::from(n..)` keeps its length of `CFIndex::MAX`, for functions which clamp the range.
+///
+/// Conversions from Rust ranges never panic: reversed ranges become empty, and lengths saturate at `CFIndex::MAX`.
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct CFRange {
     pub location: CFIndex,
     pub length: CFIndex,
 }
+impl CFRange {
+    #[inline(always)]
+    pub const fn new(location: CFIndex, length: CFIndex) -> Self {
+        Self { location, length }
+    }
+
+    /// Returns the index past the last item of the range, saturating at `CFIndex::MAX`.
+    #[inline(always)]
+    pub const fn end(&self) -> CFIndex {
+        self.location.saturating_add(self.length)
+    }
+
+    /// Converts into a range of indices, or `None` if the range is negative or overflows.
+    #[inline]
+    pub fn to_range(&self) -> Option<Range<usize>> {
+        let start = usize::try_from(self.location).ok()?;
+        let end = start.checked_add(usize::try_from(self.length).ok()?)?;
+
+        Some(start..end)
+    }
+}
 impl From<Range<CFIndex>> for CFRange {
     #[inline(always)]
     fn from(r: Range<CFIndex>) -> Self {
         Self {
             location: r.start,
-            length: r.end - r.start,
+            length: r.end.saturating_sub(r.start).max(0),
+        }
+    }
+}
+impl From<RangeInclusive<CFIndex>> for CFRange {
+    #[inline(always)]
+    fn from(r: RangeInclusive<CFIndex>) -> Self {
+        if r.is_empty() {
+            return Self {
+                location: *r.start(),
+                length: 0,
+            };
+        }
+
+        Self {
+            location: *r.start(),
+            length: r.end().saturating_sub(*r.start()).saturating_add(1),
         }
     }
 }
@@ -253,7 +806,7 @@ impl From<RangeFrom<CFIndex>> for CFRange {
     fn from(r: RangeFrom<CFIndex>) -> Self {
         Self {
             location: r.start,
-            length: CFIndex::max_value(),
+            length: CFIndex::MAX,
         }
     }
 }
@@ -267,51 +820,2540 @@ impl From<RangeTo<CFIndex>> for CFRange {
     }
 }
 impl From<RangeFull> for CFRange {
+    /// `..` is converted to `(0, 0)`, "the whole text".
     #[inline(always)]
     fn from(_: RangeFull) -> Self {
         Self { location: 0, length: 0 }
     }
 }
+impl From<CFRange> for Range<CFIndex> {
+    #[inline(always)]
+    fn from(r: CFRange) -> Self {
+        r.location..r.end()
+    }
+}
+impl TryFrom<crate::NSRange> for CFRange {
+    type Error = ();
 
-DefineCoreObject! {
-    pub CFString;
+    /// Fails if the range does not fit in the `CFIndex` domain, or the location is `NSNotFound`.
+    #[inline]
+    fn try_from(r: crate::NSRange) -> Result<Self, ()> {
+        if r.is_not_found() {
+            return Err(());
+        }
+        let location = CFIndex::try_from(r.location).map_err(drop)?;
+        let length = CFIndex::try_from(r.length).map_err(drop)?;
+        location.checked_add(length).ok_or(())?;
+
+        Ok(Self { location, length })
+    }
 }
-/// A reference to a CFString object.
-pub type CFStringRef = *const CFString;
-TollfreeBridge!(CFString = NSString);
 
-DefineCoreObject! {
-    pub CFNumber;
+/// Flags that specify how one string is compared with another.
+pub type CFOptionFlags = c_ulong;
+/// An integer type for hash codes.
+pub type CFHashCode = c_ulong;
+
+/// An integer type for constants used to specify supported string encodings in various CFString functions.
+pub type CFStringEncoding = u32;
+pub const kCFStringEncodingUTF8: CFStringEncoding = 0x0800_0100;
+
+/// Constants returned by comparison functions, indicating whether a value is equal to, less than, or greater than another value.
+pub type CFComparisonResult = CFIndex;
+pub const kCFCompareLessThan: CFComparisonResult = -1;
+pub const kCFCompareEqualTo: CFComparisonResult = 0;
+pub const kCFCompareGreaterThan: CFComparisonResult = 1;
+
+bitflags! {
+    /// A CFOptionFlags type for specifying options for string comparison.
+    pub struct CFStringCompareFlags: CFOptionFlags {
+        const CASE_INSENSITIVE = 1;
+        const BACKWARDS = 4;
+        const ANCHORED = 8;
+        const NONLITERAL = 16;
+        const LOCALIZED = 32;
+        const NUMERICALLY = 64;
+        const DIACRITIC_INSENSITIVE = 128;
+        const WIDTH_INSENSITIVE = 256;
+        const FORCED_ORDERING = 512;
+    }
 }
-/// A reference to a CFNumber object.
-pub type CFNumberRef = *mut CFNumber;
-TollfreeBridge!(CFNumber = NSNumber);
 
 DefineCoreObject! {
-    pub CFData;
+    /// Provides a suite of efficient string-manipulation and string-conversion functions.
+    pub CFString;
 }
-/// A reference to a CFData object.
-pub type CFDataRef = *const CFData;
-pub type CFMutableDataRef = *mut CFData;
-impl CFData {
+/// A reference to a CFString object.
+pub type CFStringRef = *const CFString;
+DeclareCFTypeID!(CFString = CFStringGetTypeID);
+#[cfg(feature = "std")]
+TollfreeBridge!(CFString = NSString);
+impl CFString {
+    /// Creates a string from a Rust string slice.
     #[inline(always)]
-    pub fn new(v: &[u8]) -> Option<CoreRetainedObject<Self>> {
-        unsafe { CoreRetainedObject::retained(CFDataCreate(std::ptr::null_mut(), v.as_ptr(), v.len() as _)) }
+    pub fn new(s: &str) -> Option<CoreRetainedObject<Self>> {
+        Self::new_in(s, None)
     }
-}
 
-DefineCoreObject! {
+    /// Creates a string from a Rust string slice, using the allocator (or the default allocator if `None`).
+    #[inline(always)]
+    pub fn new_in(s: &str, allocator: Option<&CFAllocator>) -> Option<CoreRetainedObject<Self>> {
+        unsafe {
+            CoreRetainedObject::retained(CFStringCreateWithBytes(
+                crate::opt_pointer(allocator) as _,
+                s.as_ptr(),
+                s.len() as _,
+                kCFStringEncodingUTF8,
+                false,
+            ))
+        }
+    }
+
+    /// Creates a string that refers a static string slice directly, without copying its contents.
+    #[inline(always)]
+    pub fn from_static(s: &'static str) -> Option<CoreRetainedObject<Self>> {
+        unsafe {
+            CoreRetainedObject::retained(CFStringCreateWithBytesNoCopy(
+                core::ptr::null_mut(),
+                s.as_ptr(),
+                s.len() as _,
+                kCFStringEncodingUTF8,
+                false,
+                kCFAllocatorNull,
+            ))
+        }
+    }
+
+    /// Returns the number of UTF-16 code units in the string.
+    #[inline(always)]
+    pub fn len(&self) -> CFIndex {
+        unsafe { CFStringGetLength(self) }
+    }
+
+    /// Returns true if the string has no characters.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the UTF-16 code unit at a given index.
+    #[inline]
+    pub fn character_at(&self, idx: CFIndex) -> Option<UniChar> {
+        if idx < 0 || idx >= self.len() {
+            return None;
+        }
+
+        Some(unsafe { CFStringGetCharacterAtIndex(self, idx) })
+    }
+
+    /// Returns the contents as a UTF-8 string slice, if the string stores them in that form internally.
+    ///
+    /// This never copies; use `to_str` for a conversion that always succeeds.
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        let p = unsafe { CFStringGetCStringPtr(self, kCFStringEncodingUTF8) };
+        if p.is_null() {
+            return None;
+        }
+
+        let s = unsafe { core::ffi::CStr::from_ptr(p) }.to_str().ok()?;
+        // contents containing NUL cannot be represented as a C string
+        if s.encode_utf16().count() != self.len() as usize {
+            return None;
+        }
+
+        Some(s)
+    }
+
+    /// Converts the contents to UTF-8, borrowing the internal storage where possible.
+    ///
+    /// Unpaired surrogates are replaced with U+FFFD.
+    pub fn to_str(&self) -> Cow<'_, str> {
+        if let Some(s) = self.as_str() {
+            return Cow::Borrowed(s);
+        }
+
+        let range = CFRange {
+            location: 0,
+            length: self.len(),
+        };
+        let mut byte_length = 0;
+        let converted = unsafe {
+            CFStringGetBytes(
+                self,
+                range.clone(),
+                kCFStringEncodingUTF8,
+                0,
+                false,
+                core::ptr::null_mut(),
+                0,
+                &mut byte_length,
+            )
+        };
+        if converted != range.length {
+            // conversion stopped at an unpaired surrogate
+            return Cow::Owned(String::from_utf16_lossy(&self.to_utf16()));
+        }
+
+        let mut buf = Vec::with_capacity(byte_length as _);
+        unsafe {
+            CFStringGetBytes(
+                self,
+                range,
+                kCFStringEncodingUTF8,
+                0,
+                false,
+                buf.as_mut_ptr(),
+                byte_length,
+                &mut byte_length,
+            );
+            buf.set_len(byte_length as _);
+        }
+
+        Cow::Owned(String::from_utf8(buf).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+    }
+
+    /// Returns the contents as UTF-16 code units, if the string stores them in that form internally.
+    #[inline]
+    pub fn as_utf16(&self) -> Option<&[UniChar]> {
+        let p = unsafe { CFStringGetCharactersPtr(self) };
+
+        if p.is_null() {
+            None
+        } else {
+            Some(unsafe { core::slice::from_raw_parts(p, self.len() as _) })
+        }
+    }
+
+    /// Returns the contents as UTF-16 code units, borrowing the internal storage where possible.
+    pub fn to_utf16(&self) -> Cow<'_, [UniChar]> {
+        if let Some(s) = self.as_utf16() {
+            return Cow::Borrowed(s);
+        }
+
+        let mut buf = vec![0; self.len() as _];
+        self.utf16_range(0, &mut buf);
+
+        Cow::Owned(buf)
+    }
+
+    /// Copies a range of UTF-16 code units into a buffer.
+    #[inline(always)]
+    pub fn utf16_range(&self, start: CFIndex, sink: &mut [UniChar]) {
+        assert!(
+            start >= 0 && start + sink.len() as CFIndex <= self.len(),
+            "range out of bounds"
+        );
+
+        unsafe {
+            CFStringGetCharacters(
+                self,
+                CFRange {
+                    location: start,
+                    length: sink.len() as _,
+                },
+                sink.as_mut_ptr(),
+            );
+        }
+    }
+
+    /// Compares the string with another string.
+    #[inline(always)]
+    pub fn compare(&self, other: &Self, options: CFStringCompareFlags) -> core::cmp::Ordering {
+        match unsafe { CFStringCompare(self, other, options.bits()) } {
+            kCFCompareLessThan => core::cmp::Ordering::Less,
+            kCFCompareEqualTo => core::cmp::Ordering::Equal,
+            _ => core::cmp::Ordering::Greater,
+        }
+    }
+}
+impl PartialEq for CFString {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.compare(other, CFStringCompareFlags::empty()) == core::cmp::Ordering::Equal
+    }
+}
+impl Eq for CFString {}
+impl PartialEq<str> for CFString {
+    #[inline(always)]
+    fn eq(&self, other: &str) -> bool {
+        self.to_str() == other
+    }
+}
+impl PartialOrd for CFString {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for CFString {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.compare(other, CFStringCompareFlags::empty())
+    }
+}
+impl core::hash::Hash for CFString {
+    #[inline(always)]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        state.write_usize(unsafe { CFHash(self as *const _ as _) } as _);
+    }
+}
+impl core::fmt::Display for CFString {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(&self.to_str())
+    }
+}
+impl core::fmt::Debug for CFString {
+    #[inline(always)]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&*self.to_str(), f)
+    }
+}
+
+/// Flags used by CFNumber to indicate the data type of a value.
+#[repr(isize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CFNumberType {
+    SInt8 = 1,
+    SInt16 = 2,
+    SInt32 = 3,
+    SInt64 = 4,
+    Float32 = 5,
+    Float64 = 6,
+    Char = 7,
+    Short = 8,
+    Int = 9,
+    Long = 10,
+    LongLong = 11,
+    Float = 12,
+    Double = 13,
+    CFIndex = 14,
+    NSInteger = 15,
+    CGFloat = 16,
+}
+impl CFNumberType {
+    /// Converts a raw type value returned from CoreFoundation.
+    pub const fn from_raw(v: CFIndex) -> Option<Self> {
+        Some(match v {
+            1 => Self::SInt8,
+            2 => Self::SInt16,
+            3 => Self::SInt32,
+            4 => Self::SInt64,
+            5 => Self::Float32,
+            6 => Self::Float64,
+            7 => Self::Char,
+            8 => Self::Short,
+            9 => Self::Int,
+            10 => Self::Long,
+            11 => Self::LongLong,
+            12 => Self::Float,
+            13 => Self::Double,
+            14 => Self::CFIndex,
+            15 => Self::NSInteger,
+            16 => Self::CGFloat,
+            _ => return None,
+        })
+    }
+}
+
+/// Primitive types that can be stored into and read back from a CFNumber.
+///
+/// # Safety
+/// `TYPE` must describe exactly the size and representation of the type: CFNumber reads and writes the value
+/// through a raw pointer according to `TYPE`, so a mismatch results in out-of-bounds or misinterpreted memory
+/// accesses. Every bit pattern written for `TYPE` must also be a valid value of the type.
+pub unsafe trait CFNumberPrimitive: Copy + Default {
+    /// The CFNumberType which has the same representation as the type.
+    const TYPE: CFNumberType;
+}
+unsafe impl CFNumberPrimitive for i8 {
+    const TYPE: CFNumberType = CFNumberType::SInt8;
+}
+unsafe impl CFNumberPrimitive for i16 {
+    const TYPE: CFNumberType = CFNumberType::SInt16;
+}
+unsafe impl CFNumberPrimitive for i32 {
+    const TYPE: CFNumberType = CFNumberType::SInt32;
+}
+unsafe impl CFNumberPrimitive for i64 {
+    const TYPE: CFNumberType = CFNumberType::SInt64;
+}
+unsafe impl CFNumberPrimitive for f32 {
+    const TYPE: CFNumberType = CFNumberType::Float32;
+}
+unsafe impl CFNumberPrimitive for f64 {
+    const TYPE: CFNumberType = CFNumberType::Float64;
+}
+
+/// A value stored in a CFNumber, tagged with its storage type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberValue {
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+impl NumberValue {
+    /// Returns the value as a 64-bit integer, truncating fractional part of floating values.
+    pub fn as_i64(&self) -> i64 {
+        match *self {
+            Self::I8(v) => v as _,
+            Self::I16(v) => v as _,
+            Self::I32(v) => v as _,
+            Self::I64(v) => v,
+            Self::F32(v) => v as _,
+            Self::F64(v) => v as _,
+        }
+    }
+
+    /// Returns the value as a 64-bit floating value.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::I8(v) => v as _,
+            Self::I16(v) => v as _,
+            Self::I32(v) => v as _,
+            Self::I64(v) => v as _,
+            Self::F32(v) => v as _,
+            Self::F64(v) => v,
+        }
+    }
+}
+
+DefineCoreObject! {
+    /// Encapsulates C scalar (numeric) types.
+    pub CFNumber;
+}
+/// A reference to a CFNumber object.
+pub type CFNumberRef = *mut CFNumber;
+DeclareCFTypeID!(CFNumber = CFNumberGetTypeID);
+#[cfg(feature = "std")]
+TollfreeBridge!(CFNumber = NSNumber);
+impl CFNumber {
+    /// Creates a CFNumber object using a specified value.
+    #[inline(always)]
+    pub fn new<T: CFNumberPrimitive>(v: T) -> Option<CoreRetainedObject<Self>> {
+        unsafe { Self::with_type(T::TYPE, &v as *const T as _) }
+    }
+
+    /// Creates a CFNumber object storing a `CGFloat` value.
+    #[inline(always)]
+    pub fn from_cgfloat(v: crate::CGFloat) -> Option<CoreRetainedObject<Self>> {
+        unsafe { Self::with_type(CFNumberType::CGFloat, &v as *const crate::CGFloat as _) }
+    }
+
+    /// Creates a CFNumber object storing a `CFIndex` value.
+    #[inline(always)]
+    pub fn from_index(v: CFIndex) -> Option<CoreRetainedObject<Self>> {
+        unsafe { Self::with_type(CFNumberType::CFIndex, &v as *const CFIndex as _) }
+    }
+
+    /// Creates a CFNumber object from a pointer to a value of the specified type.
+    #[inline(always)]
+    pub unsafe fn with_type(ty: CFNumberType, value_ptr: *const c_void) -> Option<CoreRetainedObject<Self>> {
+        CoreRetainedObject::retained(CFNumberCreate(core::ptr::null_mut(), ty as _, value_ptr))
+    }
+
+    /// Returns the type used by the CFNumber object to store its value.
+    ///
+    /// The type specified when the number was created is not necessarily preserved.
+    #[inline(always)]
+    pub fn number_type(&self) -> Option<CFNumberType> {
+        CFNumberType::from_raw(unsafe { CFNumberGetType(self as *const _ as _) })
+    }
+
+    /// Determines whether the number contains a value stored as one of the defined floating point types.
+    #[inline(always)]
+    pub fn is_float_type(&self) -> bool {
+        unsafe { CFNumberIsFloatType(self as *const _ as _) }
+    }
+
+    /// Returns the value converted to a primitive type, or `None` if the conversion was lossy.
+    #[inline]
+    pub fn get<T: CFNumberPrimitive>(&self) -> Option<T> {
+        let mut v = T::default();
+        let exact = unsafe { CFNumberGetValue(self as *const _ as _, T::TYPE as _, &mut v as *mut T as _) };
+
+        if exact {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value converted to a primitive type, even if the conversion was lossy.
+    #[inline]
+    pub fn get_lossy<T: CFNumberPrimitive>(&self) -> T {
+        let mut v = T::default();
+        unsafe {
+            CFNumberGetValue(self as *const _ as _, T::TYPE as _, &mut v as *mut T as _);
+        }
+
+        v
+    }
+
+    /// Returns the value as a `CGFloat`, or `None` if the conversion was lossy.
+    #[inline]
+    pub fn to_cgfloat(&self) -> Option<crate::CGFloat> {
+        let mut v: crate::CGFloat = 0.0;
+        let exact = unsafe {
+            CFNumberGetValue(
+                self as *const _ as _,
+                CFNumberType::CGFloat as _,
+                &mut v as *mut crate::CGFloat as _,
+            )
+        };
+
+        if exact {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value as a `CFIndex`, or `None` if the conversion was lossy.
+    #[inline]
+    pub fn to_index(&self) -> Option<CFIndex> {
+        let mut v: CFIndex = 0;
+        let exact = unsafe {
+            CFNumberGetValue(
+                self as *const _ as _,
+                CFNumberType::CFIndex as _,
+                &mut v as *mut CFIndex as _,
+            )
+        };
+
+        if exact {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the stored value, tagged with the type used to store it.
+    pub fn value(&self) -> NumberValue {
+        match self.number_type() {
+            Some(CFNumberType::SInt8 | CFNumberType::Char) => NumberValue::I8(self.get_lossy()),
+            Some(CFNumberType::SInt16 | CFNumberType::Short) => NumberValue::I16(self.get_lossy()),
+            Some(CFNumberType::SInt32 | CFNumberType::Int) => NumberValue::I32(self.get_lossy()),
+            Some(CFNumberType::Float32 | CFNumberType::Float) => NumberValue::F32(self.get_lossy()),
+            Some(CFNumberType::Float64 | CFNumberType::Double | CFNumberType::CGFloat) => {
+                NumberValue::F64(self.get_lossy())
+            }
+            Some(_) => NumberValue::I64(self.get_lossy()),
+            // private types (e.g. 128-bit integers)
+            None if self.is_float_type() => NumberValue::F64(self.get_lossy()),
+            None => NumberValue::I64(self.get_lossy()),
+        }
+    }
+}
+
+DefineCoreObject! {
+    /// Wraps boolean values for use in Core Foundation property lists and collection types.
+    pub CFBoolean;
+}
+/// A reference to a CFBoolean object.
+pub type CFBooleanRef = *const CFBoolean;
+DeclareCFTypeID!(CFBoolean = CFBooleanGetTypeID);
+impl CFBoolean {
+    /// Returns the shared boolean object for a value.
+    #[inline(always)]
+    pub fn new(v: bool) -> &'static Self {
+        unsafe { &*(if v { kCFBooleanTrue } else { kCFBooleanFalse }) }
+    }
+
+    /// Returns the value of the boolean object.
+    #[inline(always)]
+    pub fn value(&self) -> bool {
+        unsafe { CFBooleanGetValue(self) }
+    }
+}
+impl From<&CFBoolean> for bool {
+    #[inline(always)]
+    fn from(v: &CFBoolean) -> bool {
+        v.value()
+    }
+}
+
+DefineCoreObject! {
+    /// Provides support for data objects, object-oriented wrappers for byte buffers.
+    pub CFData;
+}
+/// A reference to a CFData object.
+pub type CFDataRef = *const CFData;
+DeclareCFTypeID!(CFData = CFDataGetTypeID);
+#[cfg(feature = "std")]
+TollfreeBridge!(CFData = NSData);
+/// A reference to a mutable CFData object.
+pub type CFMutableDataRef = *mut CFData;
+impl CFData {
+    /// Creates a data object by copying the bytes.
+    #[inline(always)]
+    pub fn new(v: &[u8]) -> Option<CoreRetainedObject<Self>> {
+        Self::new_in(v, None)
+    }
+
+    /// Creates a data object by copying the bytes, using the allocator (or the default allocator if `None`).
+    #[inline(always)]
+    pub fn new_in(v: &[u8], allocator: Option<&CFAllocator>) -> Option<CoreRetainedObject<Self>> {
+        unsafe {
+            CoreRetainedObject::retained(CFDataCreate(
+                crate::opt_pointer(allocator) as _,
+                v.as_ptr(),
+                v.len() as _,
+            ))
+        }
+    }
+
+    /// Creates a data object that refers a static byte slice directly, without copying its contents.
+    #[inline(always)]
+    pub fn from_static(v: &'static [u8]) -> Option<CoreRetainedObject<Self>> {
+        unsafe {
+            CoreRetainedObject::retained(CFDataCreateWithBytesNoCopy(
+                core::ptr::null_mut(),
+                v.as_ptr(),
+                v.len() as _,
+                kCFAllocatorNull,
+            ))
+        }
+    }
+
+    /// Creates a data object that takes ownership of the buffer without copying.
+    #[inline(always)]
+    pub fn from_vec(v: Vec<u8>) -> Option<CoreRetainedObject<Self>> {
+        Self::from_boxed_slice(v.into_boxed_slice())
+    }
+
+    /// Creates a data object that takes ownership of the buffer without copying.
+    ///
+    /// The buffer is freed by the Rust global allocator when the data object is deallocated.
+    pub fn from_boxed_slice(v: Box<[u8]>) -> Option<CoreRetainedObject<Self>> {
+        let (ptr, len) = (v.as_ptr(), v.len());
+        let deallocator = boxed_bytes_deallocator(v)?;
+
+        unsafe {
+            CoreRetainedObject::retained(CFDataCreateWithBytesNoCopy(
+                core::ptr::null_mut(),
+                ptr,
+                len as _,
+                deallocator.as_ptr() as _,
+            ))
+        }
+    }
+
+    /// Returns the number of bytes contained by the data object.
+    #[inline(always)]
+    pub fn len(&self) -> CFIndex {
+        unsafe { CFDataGetLength(self) }
+    }
+
+    /// Returns true if the data object contains no bytes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes of the data object.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        let p = unsafe { CFDataGetBytePtr(self) };
+        if p.is_null() {
+            return &[];
+        }
+
+        unsafe { core::slice::from_raw_parts(p, self.len() as _) }
+    }
+}
+impl AsRef<[u8]> for CFData {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.bytes()
+    }
+}
+
+/// A mutable CFData.
+#[repr(transparent)]
+pub struct CFMutableData(CFData);
+unsafe impl CoreObject for CFMutableData {}
+#[cfg(feature = "std")]
+TollfreeBridge!(mut CFMutableData = NSMutableData);
+impl core::ops::Deref for CFMutableData {
+    type Target = CFData;
+
+    #[inline(always)]
+    fn deref(&self) -> &CFData {
+        &self.0
+    }
+}
+impl CFMutableData {
+    /// Creates an empty mutable data object.
+    #[inline(always)]
+    pub fn new() -> Option<CoreRetainedMutableObject<Self>> {
+        Self::with_capacity(0)
+    }
+
+    /// Creates an empty mutable data object that holds at most `cap` bytes (0 means unlimited).
+    #[inline(always)]
+    pub fn with_capacity(cap: CFIndex) -> Option<CoreRetainedMutableObject<Self>> {
+        Self::with_capacity_in(cap, None)
+    }
+
+    /// Creates an empty mutable data object, using the allocator (or the default allocator if `None`).
+    #[inline(always)]
+    pub fn with_capacity_in(cap: CFIndex, allocator: Option<&CFAllocator>) -> Option<CoreRetainedMutableObject<Self>> {
+        unsafe {
+            CoreRetainedMutableObject::from_retained_ptr(
+                CFDataCreateMutable(crate::opt_pointer(allocator) as _, cap) as _
+            )
+        }
+    }
+
+    /// Creates a mutable data object by copying the bytes of another data object.
+    #[inline(always)]
+    pub fn copy_from(data: &CFData) -> Option<CoreRetainedMutableObject<Self>> {
+        unsafe {
+            CoreRetainedMutableObject::from_retained_ptr(CFDataCreateMutableCopy(core::ptr::null_mut(), 0, data) as _)
+        }
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&mut self) -> CFMutableDataRef {
+        self as *mut Self as _
+    }
+
+    /// Returns the bytes of the data object for modification.
+    #[inline]
+    pub fn bytes_mut(&mut self) -> &mut [u8] {
+        let len = self.len();
+        let p = unsafe { CFDataGetMutableBytePtr(self.as_mut_ptr()) };
+        if p.is_null() {
+            return &mut [];
+        }
+
+        unsafe { core::slice::from_raw_parts_mut(p, len as _) }
+    }
+
+    /// Appends the bytes to the end of the data object.
+    #[inline(always)]
+    pub fn append(&mut self, bytes: &[u8]) {
+        unsafe { CFDataAppendBytes(self.as_mut_ptr(), bytes.as_ptr(), bytes.len() as _) }
+    }
+
+    /// Resizes the data object. Extended bytes are filled with zero.
+    #[inline(always)]
+    pub fn resize(&mut self, len: CFIndex) {
+        unsafe { CFDataSetLength(self.as_mut_ptr(), len) }
+    }
+
+    /// Removes all bytes from the data object.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.resize(0);
+    }
+}
+impl AsMut<[u8]> for CFMutableData {
+    #[inline(always)]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.bytes_mut()
+    }
+}
+impl Extend<u8> for CFMutableData {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        let bytes = iter.into_iter().collect::<Vec<_>>();
+        self.append(&bytes);
+    }
+}
+impl<'a> Extend<&'a u8> for CFMutableData {
+    fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+DefineCoreObject! {
+    /// Allocates and deallocates memory for Core Foundation objects.
     pub CFAllocator;
 }
-/// A reference to a CFAllocator object.
-pub type CFAllocatorRef = *mut CFAllocator;
+/// A reference to a CFAllocator object.
+pub type CFAllocatorRef = *mut CFAllocator;
+DeclareCFTypeID!(CFAllocator = CFAllocatorGetTypeID);
+impl CFAllocator {
+    /// Returns the allocator that does nothing; used to wrap memory that must not be freed by CoreFoundation.
+    #[inline(always)]
+    pub fn null() -> &'static Self {
+        unsafe { &*kCFAllocatorNull }
+    }
+
+    /// Returns the allocator that uses `malloc`, `realloc` and `free`.
+    #[inline(always)]
+    pub fn malloc() -> &'static Self {
+        unsafe { &*kCFAllocatorMalloc }
+    }
+
+    /// Returns the system default allocator.
+    #[inline(always)]
+    pub fn system_default() -> &'static Self {
+        unsafe { &*kCFAllocatorSystemDefault }
+    }
+
+    /// Creates an allocator that allocates from the Rust global allocator.
+    #[inline(always)]
+    pub fn rust_global() -> Option<CoreRetainedObject<Self>> {
+        Self::with_global_alloc(RustGlobal)
+    }
+
+    /// Creates an allocator that allocates from a Rust allocator.
+    ///
+    /// The allocator is dropped when the CFAllocator object is deallocated.
+    pub fn with_global_alloc<A: GlobalAlloc + Send + Sync + 'static>(a: A) -> Option<CoreRetainedObject<Self>> {
+        // CoreFoundation passes only the pointer on deallocation, so each block is prefixed with its size
+        const HEADER: usize = 16;
+
+        #[inline]
+        fn layout(size: CFIndex) -> Option<Layout> {
+            let size = usize::try_from(size).ok()?.checked_add(HEADER)?;
+
+            Layout::from_size_align(size, HEADER).ok()
+        }
+        extern "C" fn release<A>(info: *const c_void) {
+            drop(unsafe { Box::from_raw(info as *mut A) });
+        }
+        extern "C" fn allocate<A: GlobalAlloc>(size: CFIndex, _: CFOptionFlags, info: *mut c_void) -> *mut c_void {
+            let Some(l) = layout(size) else {
+                return core::ptr::null_mut();
+            };
+            let p = unsafe { (*(info as *const A)).alloc(l) };
+            if p.is_null() {
+                return core::ptr::null_mut();
+            }
+
+            unsafe {
+                (p as *mut CFIndex).write(size);
+                p.add(HEADER) as _
+            }
+        }
+        extern "C" fn reallocate<A: GlobalAlloc>(
+            ptr: *mut c_void,
+            size: CFIndex,
+            _: CFOptionFlags,
+            info: *mut c_void,
+        ) -> *mut c_void {
+            let Some(new_layout) = layout(size) else {
+                return core::ptr::null_mut();
+            };
+            unsafe {
+                let base = (ptr as *mut u8).sub(HEADER);
+                let old_layout = layout((base as *const CFIndex).read()).unwrap();
+                let p = (*(info as *const A)).realloc(base, old_layout, new_layout.size());
+                if p.is_null() {
+                    return core::ptr::null_mut();
+                }
+
+                (p as *mut CFIndex).write(size);
+                p.add(HEADER) as _
+            }
+        }
+        extern "C" fn deallocate<A: GlobalAlloc>(ptr: *mut c_void, info: *mut c_void) {
+            unsafe {
+                let base = (ptr as *mut u8).sub(HEADER);
+                let l = layout((base as *const CFIndex).read()).unwrap();
+                (*(info as *const A)).dealloc(base, l);
+            }
+        }
+
+        let info = Box::into_raw(Box::new(a));
+        let ctx = CFAllocatorContext {
+            version: 0,
+            info: info as _,
+            retain: None,
+            release: Some(release::<A>),
+            copy_description: None,
+            allocate: Some(allocate::<A>),
+            reallocate: Some(reallocate::<A>),
+            deallocate: Some(deallocate::<A>),
+            preferred_size: None,
+        };
+        let r = unsafe { CoreRetainedObject::retained(CFAllocatorCreate(core::ptr::null_mut(), &ctx)) };
+        if r.is_none() {
+            drop(unsafe { Box::from_raw(info) });
+        }
+
+        r
+    }
+
+    /// Creates an allocator with the callbacks.
+    #[inline(always)]
+    pub unsafe fn with_context(ctx: &CFAllocatorContext) -> Option<CoreRetainedObject<Self>> {
+        CoreRetainedObject::retained(CFAllocatorCreate(core::ptr::null_mut(), ctx))
+    }
+
+    /// Allocates a block of memory with the allocator.
+    #[inline(always)]
+    pub fn allocate(&self, size: CFIndex) -> *mut c_void {
+        unsafe { CFAllocatorAllocate(self as *const _ as _, size, 0) }
+    }
+
+    /// Deallocates a block of memory allocated with [`CFAllocator::allocate`] of the same allocator.
+    #[inline(always)]
+    pub unsafe fn deallocate(&self, ptr: *mut c_void) {
+        CFAllocatorDeallocate(self as *const _ as _, ptr)
+    }
+}
+
+/// The Rust global allocator.
+struct RustGlobal;
+unsafe impl GlobalAlloc for RustGlobal {
+    #[inline(always)]
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        alloc::alloc::alloc(layout)
+    }
+
+    #[inline(always)]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        alloc::alloc::dealloc(ptr, layout)
+    }
+
+    #[inline(always)]
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        alloc::alloc::realloc(ptr, layout, new_size)
+    }
+}
+
+/// Callbacks and the context data of a custom allocator.
+#[repr(C)]
+pub struct CFAllocatorContext {
+    pub version: CFIndex,
+    pub info: *mut c_void,
+    pub retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    pub release: Option<extern "C" fn(info: *const c_void)>,
+    pub copy_description: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+    pub allocate: Option<extern "C" fn(size: CFIndex, hint: CFOptionFlags, info: *mut c_void) -> *mut c_void>,
+    pub reallocate:
+        Option<extern "C" fn(ptr: *mut c_void, size: CFIndex, hint: CFOptionFlags, info: *mut c_void) -> *mut c_void>,
+    pub deallocate: Option<extern "C" fn(ptr: *mut c_void, info: *mut c_void)>,
+    pub preferred_size: Option<extern "C" fn(size: CFIndex, hint: CFOptionFlags, info: *mut c_void) -> CFIndex>,
+}
+
+/// Creates an allocator that owns the buffer and frees it when the allocator itself is deallocated.
+///
+/// Used as the contents deallocator of no-copy objects: CoreFoundation keeps the allocator alive exactly as long as
+/// the contents.
+fn boxed_bytes_deallocator(v: Box<[u8]>) -> Option<CoreRetainedObject<CFAllocator>> {
+    extern "C" fn release(info: *const c_void) {
+        drop(unsafe { Box::from_raw(info as *mut Box<[u8]>) });
+    }
+    extern "C" fn allocate(_: CFIndex, _: CFOptionFlags, _: *mut c_void) -> *mut c_void {
+        core::ptr::null_mut()
+    }
+    extern "C" fn deallocate(_: *mut c_void, _: *mut c_void) {
+        // the buffer is freed on `release`
+    }
+
+    let info = Box::into_raw(Box::new(v));
+    let ctx = CFAllocatorContext {
+        version: 0,
+        info: info as _,
+        retain: None,
+        release: Some(release),
+        copy_description: None,
+        allocate: Some(allocate),
+        reallocate: None,
+        deallocate: Some(deallocate),
+        preferred_size: None,
+    };
+    let a = unsafe { CFAllocator::with_context(&ctx) };
+    if a.is_none() {
+        drop(unsafe { Box::from_raw(info) });
+    }
+
+    a
+}
+
+/// A type used to represent elapsed time in seconds.
+pub type CFTimeInterval = c_double;
+/// A type used to represent a specific point in time relative to the absolute reference date of 1 Jan 2001 00:00:00
+/// GMT.
+pub type CFAbsoluteTime = CFTimeInterval;
+
+/// A reference to a property list object (CFString, CFNumber, CFBoolean, CFDate, CFData, CFArray or CFDictionary).
+pub type CFPropertyListRef = CFTypeRef;
+
+DefineCoreObject! {
+    /// A point in time, relative to the absolute reference date of 1 Jan 2001 00:00:00 GMT.
+    pub CFDate;
+}
+/// A reference to a CFDate object.
+pub type CFDateRef = *const CFDate;
+DeclareCFTypeID!(CFDate = CFDateGetTypeID);
+#[cfg(feature = "std")]
+TollfreeBridge!(CFDate = NSDate);
+impl CFDate {
+    /// Creates a date object for the absolute time.
+    #[inline(always)]
+    pub fn new(at: CFAbsoluteTime) -> Option<CoreRetainedObject<Self>> {
+        unsafe { CoreRetainedObject::retained(CFDateCreate(core::ptr::null_mut(), at)) }
+    }
+
+    /// Creates a date object for the current time.
+    #[inline(always)]
+    pub fn now() -> Option<CoreRetainedObject<Self>> {
+        Self::new(absolute_time_now())
+    }
+
+    /// Creates a date object for the system time.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn from_system_time(t: std::time::SystemTime) -> Option<CoreRetainedObject<Self>> {
+        Self::new(absolute_time_from_system_time(t))
+    }
+
+    /// Returns the absolute time of the date object.
+    #[inline(always)]
+    pub fn absolute_time(&self) -> CFAbsoluteTime {
+        unsafe { CFDateGetAbsoluteTime(self) }
+    }
+
+    /// Returns the date as a system time, or `None` if it is out of the range of `SystemTime`.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn to_system_time(&self) -> Option<std::time::SystemTime> {
+        system_time_from_absolute_time(self.absolute_time())
+    }
+
+    /// Returns the interval between the date and another date (positive if the date is later).
+    #[inline(always)]
+    pub fn time_interval_since(&self, other: &CFDate) -> CFTimeInterval {
+        unsafe { CFDateGetTimeIntervalSinceDate(self, other) }
+    }
+}
+impl PartialEq for CFDate {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.absolute_time() == other.absolute_time()
+    }
+}
+impl PartialOrd for CFDate {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.absolute_time().partial_cmp(&other.absolute_time())
+    }
+}
+
+/// The number of seconds between the Unix epoch (1 Jan 1970) and the reference date (1 Jan 2001).
+pub const kCFAbsoluteTimeIntervalSince1970: CFTimeInterval = 978_307_200.0;
+/// The number of seconds between 1 Jan 1904 and the reference date (1 Jan 2001).
+pub const kCFAbsoluteTimeIntervalSince1904: CFTimeInterval = 3_061_152_000.0;
+const ABSOLUTE_TIME_SINCE_1970_SECS: i64 = 978_307_200;
+
+/// Converts a time since the Unix epoch to an absolute time.
+///
+/// `secs` rounds toward negative infinity and `nanos` (less than one second) is a positive offset from it, so a
+/// quarter second before the epoch is `(-1, 750_000_000)`. Whole seconds are offset as integers before the conversion
+/// to floating point, so sub-second precision is kept as far as `f64` allows.
+pub fn absolute_time_from_unix_time(secs: i64, nanos: u32) -> CFAbsoluteTime {
+    (secs as i128 - ABSOLUTE_TIME_SINCE_1970_SECS as i128) as f64 + nanos as f64 * 1e-9
+}
+
+/// Converts an absolute time to a time since the Unix epoch, rounding to the nearest nanosecond.
+///
+/// The result is in the form taken by [`absolute_time_from_unix_time`]. Returns `None` if the time is not finite or
+/// the seconds are out of the range of `i64`.
+pub fn unix_time_from_absolute_time(t: CFAbsoluteTime) -> Option<(i64, u32)> {
+    if !t.is_finite() || t.abs() >= i64::MAX as f64 {
+        return None;
+    }
+    // `f64::floor` and `f64::round` are not in `core`: casts truncate toward zero
+    let mut secs = t as i64;
+    if secs as f64 > t {
+        secs -= 1;
+    }
+    let frac = (t - secs as f64) * 1e9;
+    let mut nanos = frac as u32;
+    if frac - nanos as f64 >= 0.5 {
+        nanos += 1;
+    }
+    if nanos >= 1_000_000_000 {
+        secs += 1;
+        nanos -= 1_000_000_000;
+    }
+
+    Some((secs.checked_add(ABSOLUTE_TIME_SINCE_1970_SECS)?, nanos))
+}
+
+/// Converts a system time to an absolute time.
+#[cfg(feature = "std")]
+pub fn absolute_time_from_system_time(t: std::time::SystemTime) -> CFAbsoluteTime {
+    let (secs, nanos) = match t.duration_since(std::time::UNIX_EPOCH) {
+        Ok(d) => (i64::try_from(d.as_secs()).unwrap_or(i64::MAX), d.subsec_nanos()),
+        Err(e) => {
+            let d = e.duration();
+            let secs = 0i64.saturating_sub_unsigned(d.as_secs());
+            match d.subsec_nanos() {
+                0 => (secs, 0),
+                n => (secs.saturating_sub(1), 1_000_000_000 - n),
+            }
+        }
+    };
+
+    absolute_time_from_unix_time(secs, nanos)
+}
+
+/// Converts an absolute time to a system time, rounding to the nearest nanosecond.
+///
+/// Returns `None` if the time is not finite or is out of the range of `SystemTime`.
+#[cfg(feature = "std")]
+pub fn system_time_from_absolute_time(t: CFAbsoluteTime) -> Option<std::time::SystemTime> {
+    let (secs, nanos) = unix_time_from_absolute_time(t)?;
+    let since_epoch = std::time::Duration::new(secs.unsigned_abs(), 0);
+    let t = if secs >= 0 {
+        std::time::UNIX_EPOCH.checked_add(since_epoch)?
+    } else {
+        std::time::UNIX_EPOCH.checked_sub(since_epoch)?
+    };
+
+    t.checked_add(std::time::Duration::new(0, nanos))
+}
+
+/// Converts a time interval to a duration, rounding to the nearest nanosecond.
+///
+/// Returns `None` if the interval is negative, not finite or too large.
+#[inline]
+pub fn duration_from_time_interval(t: CFTimeInterval) -> Option<core::time::Duration> {
+    core::time::Duration::try_from_secs_f64(t).ok()
+}
+
+/// Converts a duration to a time interval.
+#[inline(always)]
+pub fn time_interval_from_duration(d: core::time::Duration) -> CFTimeInterval {
+    d.as_secs_f64()
+}
+
+/// Returns the current system absolute time.
+#[inline(always)]
+pub fn absolute_time_now() -> CFAbsoluteTime {
+    unsafe { CFAbsoluteTimeGetCurrent() }
+}
+
+/// The context shared by CFRunLoopTimer and CFRunLoopObserver.
+#[repr(C)]
+struct CFRunLoopCallbackContext {
+    version: CFIndex,
+    info: *mut c_void,
+    retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    release: Option<extern "C" fn(info: *const c_void)>,
+    copy_description: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+}
+impl CFRunLoopCallbackContext {
+    /// Creates a context which owns a boxed `CalloutCell<T>`, dropping it when the owner object is deallocated.
+    fn boxed<T>(v: T) -> Self {
+        extern "C" fn release<T>(info: *const c_void) {
+            drop(unsafe { Box::from_raw(info as *mut CalloutCell<T>) });
+        }
+
+        Self {
+            version: 0,
+            info: Box::into_raw(Box::new(CalloutCell::new(v))) as _,
+            retain: None,
+            release: Some(release::<T>),
+            copy_description: None,
+        }
+    }
+
+    /// Drops the boxed value owned by the context, if the owner object was not created.
+    unsafe fn discard<T>(self) {
+        drop(Box::from_raw(self.info as *mut CalloutCell<T>));
+    }
+}
+
+/// A closure called from run loop callouts, guarded by an atomic flag.
+///
+/// A callout can re-enter while the closure is running (e.g. from a nested run loop), and a source added to run
+/// loops of several threads can be performed concurrently; a callout which finds the closure running is skipped.
+struct CalloutCell<F> {
+    running: AtomicBool,
+    f: UnsafeCell<F>,
+}
+impl<F> CalloutCell<F> {
+    const fn new(f: F) -> Self {
+        Self {
+            running: AtomicBool::new(false),
+            f: UnsafeCell::new(f),
+        }
+    }
+}
+
+/// Calls the closure in a `CalloutCell<F>` passed as the info of a callback, unless it is already running.
+#[inline(always)]
+unsafe fn call_info<F, R>(info: *mut c_void, f: impl FnOnce(&mut F) -> R) -> Option<R> {
+    let cell = &*(info as *const CalloutCell<F>);
+    if cell.running.swap(true, Ordering::Acquire) {
+        return None;
+    }
+    let r = f(&mut *cell.f.get());
+    cell.running.store(false, Ordering::Release);
+
+    Some(r)
+}
+
+/// The result of running a run loop.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFRunLoopRunResult {
+    /// The run loop mode has no sources or timers.
+    Finished = 1,
+    /// The run loop was stopped with [`CFRunLoop::stop`].
+    Stopped = 2,
+    /// The time interval passed.
+    TimedOut = 3,
+    /// A source was processed (only when `return_after_source_handled` is true).
+    HandledSource = 4,
+}
+
+bitflags! {
+    /// Run loop activity stages in which run loop observers are scheduled.
+    pub struct CFRunLoopActivity: CFOptionFlags {
+        const ENTRY = 1 << 0;
+        const BEFORE_TIMERS = 1 << 1;
+        const BEFORE_SOURCES = 1 << 2;
+        const BEFORE_WAITING = 1 << 5;
+        const AFTER_WAITING = 1 << 6;
+        const EXIT = 1 << 7;
+        const ALL_ACTIVITIES = 0x0FFF_FFFF;
+    }
+}
+
+DefineCoreObject! {
+    /// An event processing loop of a thread, which monitors sources of input and timers.
+    pub CFRunLoop;
+}
+/// A reference to a run loop object.
+pub type CFRunLoopRef = *mut CFRunLoop;
+DeclareCFTypeID!(CFRunLoop = CFRunLoopGetTypeID);
+/// A name of a run loop mode.
+pub type CFRunLoopMode = CFStringRef;
+impl CFRunLoop {
+    /// Returns the run loop of the current thread.
+    #[inline(always)]
+    pub fn current() -> CoreRetainedObject<Self> {
+        unsafe { CoreRetainedObject::retained_unchecked(cfretain(CFRunLoopGetCurrent())) }
+    }
+
+    /// Returns the main run loop.
+    #[inline(always)]
+    pub fn main() -> &'static Self {
+        unsafe { &*CFRunLoopGetMain() }
+    }
+
+    /// The mode to deal with objects while the run loop is idle.
+    #[inline(always)]
+    pub fn default_mode() -> &'static CFString {
+        unsafe { &*kCFRunLoopDefaultMode }
+    }
+
+    /// The pseudo-mode of the set of "common" modes. Objects added to this mode are monitored by all common modes.
+    #[inline(always)]
+    pub fn common_modes() -> &'static CFString {
+        unsafe { &*kCFRunLoopCommonModes }
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&self) -> CFRunLoopRef {
+        self as *const Self as _
+    }
+
+    /// Runs the run loop of the current thread in the default mode indefinitely.
+    #[inline(always)]
+    pub fn run() {
+        unsafe { CFRunLoopRun() }
+    }
+
+    /// Runs the run loop of the current thread in a particular mode.
+    #[inline(always)]
+    pub fn run_in_mode(
+        mode: &CFString,
+        seconds: CFTimeInterval,
+        return_after_source_handled: bool,
+    ) -> CFRunLoopRunResult {
+        match unsafe { CFRunLoopRunInMode(mode, seconds, return_after_source_handled) } {
+            1 => CFRunLoopRunResult::Finished,
+            2 => CFRunLoopRunResult::Stopped,
+            3 => CFRunLoopRunResult::TimedOut,
+            _ => CFRunLoopRunResult::HandledSource,
+        }
+    }
+
+    /// Forces the run loop to stop running.
+    #[inline(always)]
+    pub fn stop(&self) {
+        unsafe { CFRunLoopStop(self.as_mut_ptr()) }
+    }
+
+    /// Wakes the run loop that is waiting for an event.
+    #[inline(always)]
+    pub fn wake_up(&self) {
+        unsafe { CFRunLoopWakeUp(self.as_mut_ptr()) }
+    }
+
+    /// Returns true if the run loop is waiting for an event.
+    #[inline(always)]
+    pub fn is_waiting(&self) -> bool {
+        unsafe { CFRunLoopIsWaiting(self.as_mut_ptr()) }
+    }
+
+    /// Returns the name of the mode in which the run loop is running.
+    #[inline(always)]
+    pub fn current_mode(&self) -> Option<CoreRetainedObject<CFString>> {
+        unsafe { CoreRetainedObject::retained(CFRunLoopCopyCurrentMode(self.as_mut_ptr())) }
+    }
+
+    /// Adds a timer to a run loop mode.
+    #[inline(always)]
+    pub fn add_timer(&self, timer: &CFRunLoopTimer, mode: &CFString) {
+        unsafe { CFRunLoopAddTimer(self.as_mut_ptr(), timer as *const _ as _, mode) }
+    }
+
+    /// Removes a timer from a run loop mode.
+    #[inline(always)]
+    pub fn remove_timer(&self, timer: &CFRunLoopTimer, mode: &CFString) {
+        unsafe { CFRunLoopRemoveTimer(self.as_mut_ptr(), timer as *const _ as _, mode) }
+    }
+
+    /// Adds a source to a run loop mode.
+    #[inline(always)]
+    pub fn add_source(&self, source: &CFRunLoopSource, mode: &CFString) {
+        unsafe { CFRunLoopAddSource(self.as_mut_ptr(), source as *const _ as _, mode) }
+    }
+
+    /// Removes a source from a run loop mode.
+    #[inline(always)]
+    pub fn remove_source(&self, source: &CFRunLoopSource, mode: &CFString) {
+        unsafe { CFRunLoopRemoveSource(self.as_mut_ptr(), source as *const _ as _, mode) }
+    }
+
+    /// Adds an observer to a run loop mode.
+    #[inline(always)]
+    pub fn add_observer(&self, observer: &CFRunLoopObserver, mode: &CFString) {
+        unsafe { CFRunLoopAddObserver(self.as_mut_ptr(), observer as *const _ as _, mode) }
+    }
+
+    /// Removes an observer from a run loop mode.
+    #[inline(always)]
+    pub fn remove_observer(&self, observer: &CFRunLoopObserver, mode: &CFString) {
+        unsafe { CFRunLoopRemoveObserver(self.as_mut_ptr(), observer as *const _ as _, mode) }
+    }
+}
+
+DefineCoreObject! {
+    /// A timer that fires on a run loop.
+    pub CFRunLoopTimer;
+}
+/// A reference to a run loop timer object.
+pub type CFRunLoopTimerRef = *mut CFRunLoopTimer;
+DeclareCFTypeID!(CFRunLoopTimer = CFRunLoopTimerGetTypeID);
+impl CFRunLoopTimer {
+    /// Creates a timer which calls the closure first at `fire_date`, then every `interval` seconds
+    /// (or only once if `interval` is 0).
+    #[inline(always)]
+    pub fn new<F: FnMut(&CFRunLoopTimer) + Send + 'static>(
+        fire_date: CFAbsoluteTime,
+        interval: CFTimeInterval,
+        callback: F,
+    ) -> Option<CoreRetainedObject<Self>> {
+        unsafe { Self::new_unchecked(fire_date, interval, callback) }
+    }
+
+    /// Creates a timer which calls a closure that is not `Send`.
+    ///
+    /// # Safety
+    /// The timer must only be added to the run loop of the current thread.
+    pub unsafe fn new_unchecked<F: FnMut(&CFRunLoopTimer) + 'static>(
+        fire_date: CFAbsoluteTime,
+        interval: CFTimeInterval,
+        callback: F,
+    ) -> Option<CoreRetainedObject<Self>> {
+        extern "C" fn callout<F: FnMut(&CFRunLoopTimer)>(timer: CFRunLoopTimerRef, info: *mut c_void) {
+            unsafe { call_info::<F, _>(info, |f| f(&*timer)) };
+        }
+
+        let mut ctx = CFRunLoopCallbackContext::boxed(callback);
+        let t = CoreRetainedObject::retained(CFRunLoopTimerCreate(
+            core::ptr::null_mut(),
+            fire_date,
+            interval,
+            0,
+            0,
+            callout::<F>,
+            &mut ctx,
+        ));
+        if t.is_none() {
+            ctx.discard::<F>();
+        }
+
+        t
+    }
+
+    /// Creates a timer which calls the closure first after `delay` seconds, then every `interval` seconds
+    /// (or only once if `interval` is 0).
+    #[inline(always)]
+    pub fn after<F: FnMut(&CFRunLoopTimer) + Send + 'static>(
+        delay: CFTimeInterval,
+        interval: CFTimeInterval,
+        callback: F,
+    ) -> Option<CoreRetainedObject<Self>> {
+        Self::new(absolute_time_now() + delay, interval, callback)
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&self) -> CFRunLoopTimerRef {
+        self as *const Self as _
+    }
+
+    /// Stops the timer from ever firing again and removes it from all run loop modes.
+    #[inline(always)]
+    pub fn invalidate(&self) {
+        unsafe { CFRunLoopTimerInvalidate(self.as_mut_ptr()) }
+    }
+
+    /// Returns true if the timer is valid and able to fire.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        unsafe { CFRunLoopTimerIsValid(self.as_mut_ptr()) }
+    }
+
+    /// Returns the firing interval of a repeating timer.
+    #[inline(always)]
+    pub fn interval(&self) -> CFTimeInterval {
+        unsafe { CFRunLoopTimerGetInterval(self.as_mut_ptr()) }
+    }
+
+    /// Returns the next firing time of the timer.
+    #[inline(always)]
+    pub fn next_fire_date(&self) -> CFAbsoluteTime {
+        unsafe { CFRunLoopTimerGetNextFireDate(self.as_mut_ptr()) }
+    }
+
+    /// Sets the next firing time of the timer.
+    #[inline(always)]
+    pub fn set_next_fire_date(&self, date: CFAbsoluteTime) {
+        unsafe { CFRunLoopTimerSetNextFireDate(self.as_mut_ptr(), date) }
+    }
+}
+
+#[repr(C)]
+struct CFRunLoopSourceContext {
+    version: CFIndex,
+    info: *mut c_void,
+    retain: Option<extern "C" fn(info: *const c_void) -> *const c_void>,
+    release: Option<extern "C" fn(info: *const c_void)>,
+    copy_description: Option<extern "C" fn(info: *const c_void) -> CFStringRef>,
+    equal: Option<extern "C" fn(info1: *const c_void, info2: *const c_void) -> bool>,
+    hash: Option<extern "C" fn(info: *const c_void) -> CFHashCode>,
+    schedule: Option<extern "C" fn(info: *mut c_void, rl: CFRunLoopRef, mode: CFRunLoopMode)>,
+    cancel: Option<extern "C" fn(info: *mut c_void, rl: CFRunLoopRef, mode: CFRunLoopMode)>,
+    perform: Option<extern "C" fn(info: *mut c_void)>,
+}
+
+DefineCoreObject! {
+    /// An input source of a run loop.
+    pub CFRunLoopSource;
+}
+/// A reference to a run loop source object.
+pub type CFRunLoopSourceRef = *mut CFRunLoopSource;
+DeclareCFTypeID!(CFRunLoopSource = CFRunLoopSourceGetTypeID);
+impl CFRunLoopSource {
+    /// Creates a custom (version 0) source which calls the closure when it is signaled.
+    ///
+    /// The closure is called on the thread of the run loop the source is added to.
+    #[inline(always)]
+    pub fn new<F: FnMut() + Send + 'static>(order: CFIndex, perform: F) -> Option<CoreRetainedObject<Self>> {
+        unsafe { Self::new_unchecked(order, perform) }
+    }
+
+    /// Creates a custom (version 0) source which calls a closure that is not `Send`.
+    ///
+    /// # Safety
+    /// The source must only be added to the run loop of the current thread.
+    pub unsafe fn new_unchecked<F: FnMut() + 'static>(order: CFIndex, perform: F) -> Option<CoreRetainedObject<Self>> {
+        extern "C" fn release<F>(info: *const c_void) {
+            drop(unsafe { Box::from_raw(info as *mut CalloutCell<F>) });
+        }
+        extern "C" fn callout<F: FnMut()>(info: *mut c_void) {
+            unsafe { call_info::<F, _>(info, |f| f()) };
+        }
+
+        let info = Box::into_raw(Box::new(CalloutCell::new(perform)));
+        let mut ctx = CFRunLoopSourceContext {
+            version: 0,
+            info: info as _,
+            retain: None,
+            release: Some(release::<F>),
+            copy_description: None,
+            equal: None,
+            hash: None,
+            schedule: None,
+            cancel: None,
+            perform: Some(callout::<F>),
+        };
+        let s = CoreRetainedObject::retained(CFRunLoopSourceCreate(core::ptr::null_mut(), order, &mut ctx));
+        if s.is_none() {
+            drop(Box::from_raw(info));
+        }
+
+        s
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&self) -> CFRunLoopSourceRef {
+        self as *const Self as _
+    }
+
+    /// Marks the source as ready to fire. The run loop must be woken up if it is waiting.
+    #[inline(always)]
+    pub fn signal(&self) {
+        unsafe { CFRunLoopSourceSignal(self.as_mut_ptr()) }
+    }
+
+    /// Removes the source from all run loop modes, so that it never fires again.
+    #[inline(always)]
+    pub fn invalidate(&self) {
+        unsafe { CFRunLoopSourceInvalidate(self.as_mut_ptr()) }
+    }
+
+    /// Returns true if the source is valid and able to fire.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        unsafe { CFRunLoopSourceIsValid(self.as_mut_ptr()) }
+    }
+
+    /// Creates a handle that signals the source and wakes the run loop up from any thread.
+    #[inline(always)]
+    pub fn waker(&self, run_loop: &CFRunLoop) -> CFRunLoopWaker {
+        unsafe {
+            CFRetain(self as *const _ as _);
+            CFRetain(run_loop as *const _ as _);
+        }
+
+        CFRunLoopWaker {
+            source: self as *const _,
+            run_loop: run_loop as *const _,
+        }
+    }
+}
+
+/// A thread-safe handle that fires a run loop source.
+pub struct CFRunLoopWaker {
+    source: *const CFRunLoopSource,
+    run_loop: *const CFRunLoop,
+}
+// signaling a source and waking up a run loop are thread-safe operations
+unsafe impl Send for CFRunLoopWaker {}
+unsafe impl Sync for CFRunLoopWaker {}
+impl CFRunLoopWaker {
+    /// Signals the source and wakes the run loop up.
+    #[inline]
+    pub fn wake(&self) {
+        unsafe {
+            (*self.source).signal();
+            (*self.run_loop).wake_up();
+        }
+    }
+}
+impl Clone for CFRunLoopWaker {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        unsafe {
+            CFRetain(self.source as _);
+            CFRetain(self.run_loop as _);
+        }
+
+        Self {
+            source: self.source,
+            run_loop: self.run_loop,
+        }
+    }
+}
+impl Drop for CFRunLoopWaker {
+    #[inline(always)]
+    fn drop(&mut self) {
+        unsafe {
+            CFRelease(self.source as _);
+            CFRelease(self.run_loop as _);
+        }
+    }
+}
+
+DefineCoreObject! {
+    /// An observer of the activities of a run loop.
+    pub CFRunLoopObserver;
+}
+/// A reference to a run loop observer object.
+pub type CFRunLoopObserverRef = *mut CFRunLoopObserver;
+DeclareCFTypeID!(CFRunLoopObserver = CFRunLoopObserverGetTypeID);
+impl CFRunLoopObserver {
+    /// Creates an observer which calls the closure at the activity stages of a run loop.
+    #[inline(always)]
+    pub fn new<F: FnMut(&CFRunLoopObserver, CFRunLoopActivity) + Send + 'static>(
+        activities: CFRunLoopActivity,
+        repeats: bool,
+        order: CFIndex,
+        callback: F,
+    ) -> Option<CoreRetainedObject<Self>> {
+        unsafe { Self::new_unchecked(activities, repeats, order, callback) }
+    }
+
+    /// Creates an observer which calls a closure that is not `Send`.
+    ///
+    /// # Safety
+    /// The observer must only be added to the run loop of the current thread.
+    pub unsafe fn new_unchecked<F: FnMut(&CFRunLoopObserver, CFRunLoopActivity) + 'static>(
+        activities: CFRunLoopActivity,
+        repeats: bool,
+        order: CFIndex,
+        callback: F,
+    ) -> Option<CoreRetainedObject<Self>> {
+        extern "C" fn callout<F: FnMut(&CFRunLoopObserver, CFRunLoopActivity)>(
+            observer: CFRunLoopObserverRef,
+            activity: CFOptionFlags,
+            info: *mut c_void,
+        ) {
+            unsafe {
+                call_info::<F, _>(info, |f| f(&*observer, CFRunLoopActivity::from_bits_truncate(activity)));
+            }
+        }
+
+        let mut ctx = CFRunLoopCallbackContext::boxed(callback);
+        let o = CoreRetainedObject::retained(CFRunLoopObserverCreate(
+            core::ptr::null_mut(),
+            activities.bits(),
+            repeats,
+            order,
+            callout::<F>,
+            &mut ctx,
+        ));
+        if o.is_none() {
+            ctx.discard::<F>();
+        }
+
+        o
+    }
+
+    /// Creates an observer which calls the closure every time just before the run loop waits for an event.
+    #[inline(always)]
+    pub fn before_waiting<F: FnMut() + Send + 'static>(mut callback: F) -> Option<CoreRetainedObject<Self>> {
+        Self::new(CFRunLoopActivity::BEFORE_WAITING, true, 0, move |_, _| callback())
+    }
+
+    #[inline(always)]
+    fn as_mut_ptr(&self) -> CFRunLoopObserverRef {
+        self as *const Self as _
+    }
+
+    /// Stops the observer from ever firing again and removes it from all run loop modes.
+    #[inline(always)]
+    pub fn invalidate(&self) {
+        unsafe { CFRunLoopObserverInvalidate(self.as_mut_ptr()) }
+    }
+
+    /// Returns true if the observer is valid and able to fire.
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        unsafe { CFRunLoopObserverIsValid(self.as_mut_ptr()) }
+    }
+}
+
+/// Options to specify different path styles for use with file URLs.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFURLPathStyle {
+    POSIX = 0,
+    Windows = 2,
+}
+
+DefineCoreObject! {
+    /// A reference to a local file or a remote resource.
+    pub CFURL;
+}
+/// A reference to a CFURL object.
+pub type CFURLRef = *const CFURL;
+DeclareCFTypeID!(CFURL = CFURLGetTypeID);
+#[cfg(feature = "std")]
+TollfreeBridge!(CFURL = NSURL);
+impl CFURL {
+    /// Creates a URL by parsing a string, or returns `None` if the string is not a valid URL.
+    ///
+    /// The string must already be percent-encoded where needed: characters such as spaces make the URL invalid.
+    #[inline(always)]
+    pub fn new(url: &str) -> Option<CoreRetainedObject<Self>> {
+        unsafe {
+            CoreRetainedObject::retained(CFURLCreateWithBytes(
+                core::ptr::null_mut(),
+                url.as_ptr(),
+                url.len() as _,
+                kCFStringEncodingUTF8,
+                core::ptr::null(),
+            ))
+        }
+    }
+
+    /// Creates a file URL for a path. Relative paths are resolved against the current directory.
+    ///
+    /// The path bytes are used as-is, and percent-encoded in the URL where needed (see [`file_url_from_path`]).
+    #[cfg(feature = "std")]
+    pub fn from_file_path(path: &std::path::Path, is_directory: bool) -> Option<CoreRetainedObject<Self>> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let absolute;
+        let path = if path.is_absolute() {
+            path
+        } else {
+            absolute = std::env::current_dir().ok()?.join(path);
+            &absolute
+        };
+
+        Self::new(&file_url_from_path(path.as_os_str().as_bytes(), is_directory)?)
+    }
+
+    /// Returns the path of a file URL in the file system representation, or `None` if the URL is not a file URL or
+    /// the path does not fit in `PATH_MAX` bytes.
+    ///
+    /// Relative URLs are resolved against their base first (see [`path_from_file_url`]).
+    #[cfg(feature = "std")]
+    pub fn to_file_path(&self) -> Option<std::path::PathBuf> {
+        use std::os::unix::ffi::OsStringExt;
+
+        let absolute = unsafe { CoreRetainedObject::retained(CFURLCopyAbsoluteURL(self))? };
+        let path = path_from_file_url(&absolute.string().to_str())?;
+        if path.len() >= PATH_MAX as usize {
+            return None;
+        }
+
+        Some(std::ffi::OsString::from_vec(path).into())
+    }
+
+    /// Returns the URL as a string.
+    #[inline(always)]
+    pub fn string(&self) -> &CFString {
+        unsafe { &*CFURLGetString(self) }
+    }
+
+    /// Returns the scheme of the URL (e.g. `https`, `file`).
+    #[inline(always)]
+    pub fn scheme(&self) -> Option<CoreRetainedObject<CFString>> {
+        unsafe { CoreRetainedObject::retained(CFURLCopyScheme(self)) }
+    }
+
+    /// Returns the host of the URL.
+    #[inline(always)]
+    pub fn host(&self) -> Option<CoreRetainedObject<CFString>> {
+        unsafe { CoreRetainedObject::retained(CFURLCopyHostName(self)) }
+    }
+
+    /// Returns the port number of the URL, if specified.
+    #[inline]
+    pub fn port(&self) -> Option<i32> {
+        let p = unsafe { CFURLGetPortNumber(self) };
+        if p < 0 {
+            None
+        } else {
+            Some(p)
+        }
+    }
+
+    /// Returns the path of the URL, with percent escapes decoded.
+    #[inline(always)]
+    pub fn path(&self) -> Option<CoreRetainedObject<CFString>> {
+        unsafe { CoreRetainedObject::retained(CFURLCopyFileSystemPath(self, CFURLPathStyle::POSIX)) }
+    }
+
+    /// Returns true if the path of the URL ends with a slash.
+    #[inline(always)]
+    pub fn has_directory_path(&self) -> bool {
+        unsafe { CFURLHasDirectoryPath(self) }
+    }
+
+    /// Makes the resource pointed by a security-scoped URL available to the app.
+    ///
+    /// Returns false if the URL is not security-scoped or the access is denied. Every successful call must be
+    /// balanced by [`CFURL::stop_accessing_security_scoped_resource`].
+    #[inline(always)]
+    pub fn start_accessing_security_scoped_resource(&self) -> bool {
+        unsafe { CFURLStartAccessingSecurityScopedResource(self) }
+    }
+
+    /// Revokes the access granted by [`CFURL::start_accessing_security_scoped_resource`].
+    #[inline(always)]
+    pub fn stop_accessing_security_scoped_resource(&self) {
+        unsafe { CFURLStopAccessingSecurityScopedResource(self) }
+    }
+}
+
+/// Returns whether a byte is kept as-is in the path of a file URL.
+///
+/// `;` is escaped too, as it delimits parameters in older URL syntaxes.
+const fn is_file_url_path_byte(b: u8) -> bool {
+    matches!(b, b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' | b'@' | b'!' | b'$')
+        || matches!(b, b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b'=')
+}
+
+/// Builds the string of a file URL for an absolute path in the file system representation.
+///
+/// Bytes other than unreserved and path characters (including spaces, `%`, `#`, `?` and non-UTF-8 bytes) are
+/// percent-encoded. A slash is appended to directories. Returns `None` if the path is not absolute.
+pub fn file_url_from_path(path: &[u8], is_directory: bool) -> Option<String> {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    if path.first() != Some(&b'/') {
+        return None;
+    }
+    let mut url = String::with_capacity(path.len() + 8);
+    url.push_str("file://");
+    for &b in path {
+        if is_file_url_path_byte(b) {
+            url.push(b as char);
+        } else {
+            url.push('%');
+            url.push(HEX[(b >> 4) as usize] as char);
+            url.push(HEX[(b & 0x0f) as usize] as char);
+        }
+    }
+    if is_directory && !url.ends_with('/') {
+        url.push('/');
+    }
+
+    Some(url)
+}
+
+/// Returns the path of an absolute file URL string in the file system representation, decoding percent escapes.
+///
+/// The query and fragment are ignored, and the trailing slash of directories is removed. Returns `None` if the
+/// scheme is not `file`, the host is not empty or `localhost`, an escape is malformed or the path contains a NUL.
+pub fn path_from_file_url(url: &str) -> Option<Vec<u8>> {
+    const fn hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let (scheme, rest) = url.split_once(':')?;
+    if !scheme.eq_ignore_ascii_case("file") {
+        return None;
+    }
+    let rest = rest.split(['?', '#']).next().unwrap_or("");
+    let path = match rest.strip_prefix("//") {
+        Some(r) => {
+            let (host, path) = r.split_at(r.find('/')?);
+            if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+                return None;
+            }
+            path
+        }
+        None => rest,
+    };
+    if !path.starts_with('/') {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut src = path.bytes();
+    while let Some(b) = src.next() {
+        let b = if b == b'%' {
+            (hex(src.next()?)? << 4) | hex(src.next()?)?
+        } else {
+            b
+        };
+        if b == 0 {
+            return None;
+        }
+        bytes.push(b);
+    }
+    if bytes.len() > 1 && bytes.ends_with(b"/") {
+        bytes.pop();
+    }
+
+    Some(bytes)
+}
+
+/// The name of a notification.
+pub type CFNotificationName = CFStringRef;
+
+DefineCoreObject! {
+    /// A notification dispatch table, which delivers notifications to the registered observers.
+    pub CFNotificationCenter;
+}
+/// A reference to a notification center.
+pub type CFNotificationCenterRef = *mut CFNotificationCenter;
+DeclareCFTypeID!(CFNotificationCenter = CFNotificationCenterGetTypeID);
+impl CFNotificationCenter {
+    /// Returns the local notification center of the app, which is the same as `NSNotificationCenter.defaultCenter`.
+    #[inline(always)]
+    pub fn local() -> &'static Self {
+        unsafe { &*CFNotificationCenterGetLocalCenter() }
+    }
+
+    /// Registers a callback for the notifications with the name, posted by any object.
+    ///
+    /// The callback runs on the thread which posted the notification, and receives the name of the notification.
+    /// Deliveries on different threads run one at a time, and notifications posted from the callback itself are not
+    /// delivered to it.
+    /// The registration lasts until the returned observer is dropped.
+    #[cfg(feature = "std")]
+    pub fn add_observer<F: FnMut(&CFString) + Send + 'static>(
+        &'static self,
+        name: &CFString,
+        callback: F,
+    ) -> CFNotificationObserver {
+        extern "C" fn call(
+            _: CFNotificationCenterRef,
+            observer: *mut c_void,
+            name: CFNotificationName,
+            _: *const c_void,
+            _: CFDictionaryRef,
+        ) {
+            let slot = unsafe { &*(observer as *const ObserverSlot) };
+            let Some(name) = (unsafe { name.as_ref() }) else {
+                return;
+            };
+
+            // the callback is taken out of the slot while it runs, so that it can post notifications or remove the
+            // observer without deadlocking
+            let this = std::thread::current().id();
+            let mut s = slot.state.lock().unwrap();
+            let mut f = loop {
+                match s.running {
+                    // a notification posted by the callback itself is not delivered to it
+                    Some(t) if t == this => return,
+                    Some(_) => s = slot.done.wait(s).unwrap(),
+                    None => match s.callback.take() {
+                        Some(f) => break f,
+                        None => return,
+                    },
+                }
+            };
+            s.running = Some(this);
+            drop(s);
+
+            f(name);
+
+            let mut s = slot.state.lock().unwrap();
+            s.running = None;
+            let f = if s.removed { Some(f) } else { s.callback.replace(f) };
+            drop(s);
+            slot.done.notify_all();
+            drop(f);
+        }
+
+        // deliveries on other threads may still be in flight after the removal, so the slot is never freed
+        let slot: &'static ObserverSlot = Box::leak(Box::new(ObserverSlot {
+            state: std::sync::Mutex::new(ObserverState {
+                callback: Some(Box::new(callback)),
+                running: None,
+                removed: false,
+            }),
+            done: std::sync::Condvar::new(),
+        }));
+        unsafe {
+            CFNotificationCenterAddObserver(
+                self as *const Self as _,
+                slot as *const ObserverSlot as _,
+                call,
+                name,
+                core::ptr::null(),
+                CFNotificationSuspensionBehavior::DeliverImmediately as _,
+            );
+        }
+
+        CFNotificationObserver { center: self, slot }
+    }
+}
+
+/// Delivery behaviors for notifications posted while the app is in the background.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CFNotificationSuspensionBehavior {
+    Drop = 1,
+    Coalesce = 2,
+    Hold = 3,
+    DeliverImmediately = 4,
+}
+
+#[cfg(feature = "std")]
+struct ObserverSlot {
+    state: std::sync::Mutex<ObserverState>,
+    /// Notified when a delivery finishes.
+    done: std::sync::Condvar,
+}
+#[cfg(feature = "std")]
+type ObserverCallback = Box<dyn FnMut(&CFString) + Send>;
+#[cfg(feature = "std")]
+struct ObserverState {
+    /// The callback, or `None` while it runs or after the removal.
+    callback: Option<ObserverCallback>,
+    /// The thread running the callback.
+    running: Option<std::thread::ThreadId>,
+    removed: bool,
+}
+
+/// A callback registration of a notification center, removed on drop.
+///
+/// The callback is dropped with the registration, but a small slot of a few words is leaked for each registration,
+/// as CoreFoundation may still deliver a notification posted on another thread with it after the removal.
+#[cfg(feature = "std")]
+pub struct CFNotificationObserver {
+    center: &'static CFNotificationCenter,
+    slot: &'static ObserverSlot,
+}
+#[cfg(feature = "std")]
+impl Drop for CFNotificationObserver {
+    fn drop(&mut self) {
+        unsafe {
+            CFNotificationCenterRemoveEveryObserver(
+                self.center as *const CFNotificationCenter as _,
+                self.slot as *const ObserverSlot as _,
+            );
+        }
+
+        let this = std::thread::current().id();
+        let mut s = self.slot.state.lock().unwrap();
+        s.removed = true;
+        let f = s.callback.take();
+        // waits for the delivery in progress on another thread, if any; a delivery on this thread (dropping the
+        // observer from its callback) drops the callback when it returns
+        while s.running.is_some_and(|t| t != this) {
+            s = self.slot.done.wait(s).unwrap();
+        }
+        drop(s);
+        drop(f);
+    }
+}
 
-#[link(name = "CoreFoundation", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "CoreFoundation", kind = "framework"))]
 unsafe extern "system" {
     unsafe fn CFRetain(cf: CFTypeRef) -> CFTypeRef;
     unsafe fn CFRelease(cf: CFTypeRef);
+    unsafe fn CFGetTypeID(cf: CFTypeRef) -> CFTypeID;
+    unsafe fn CFCopyDescription(cf: CFTypeRef) -> CFStringRef;
+    unsafe fn CFArrayGetTypeID() -> CFTypeID;
+    unsafe fn CFDictionaryGetTypeID() -> CFTypeID;
+    unsafe fn CFAttributedStringGetTypeID() -> CFTypeID;
+    unsafe fn CFStringGetTypeID() -> CFTypeID;
+    unsafe fn CFNumberGetTypeID() -> CFTypeID;
+    unsafe fn CFBooleanGetTypeID() -> CFTypeID;
+    unsafe fn CFDataGetTypeID() -> CFTypeID;
+    unsafe fn CFAllocatorGetTypeID() -> CFTypeID;
+    unsafe fn CFHash(cf: CFTypeRef) -> CFHashCode;
     unsafe fn CFArrayGetCount(array: CFArrayRef) -> CFIndex;
     unsafe fn CFArrayGetValueAtIndex(array: CFArrayRef, idx: CFIndex) -> *const c_void;
+    unsafe fn CFArrayCreate(
+        allocator: CFAllocatorRef,
+        values: *const *const c_void,
+        num_values: CFIndex,
+        callbacks: *const CFArrayCallBacks,
+    ) -> CFArrayRef;
+    unsafe fn CFArrayCreateMutable(
+        allocator: CFAllocatorRef,
+        capacity: CFIndex,
+        callbacks: *const CFArrayCallBacks,
+    ) -> CFMutableArrayRef;
+    unsafe fn CFArrayAppendValue(array: CFMutableArrayRef, value: *const c_void);
+    unsafe fn CFArrayInsertValueAtIndex(array: CFMutableArrayRef, idx: CFIndex, value: *const c_void);
+    unsafe fn CFArraySetValueAtIndex(array: CFMutableArrayRef, idx: CFIndex, value: *const c_void);
+    unsafe fn CFArrayRemoveValueAtIndex(array: CFMutableArrayRef, idx: CFIndex);
+    unsafe fn CFArrayRemoveAllValues(array: CFMutableArrayRef);
     unsafe fn CFDictionaryGetValue(dict: CFDictionaryRef, key: *const c_void) -> *const c_void;
+    unsafe fn CFDictionaryGetCount(dict: CFDictionaryRef) -> CFIndex;
+    unsafe fn CFDictionaryContainsKey(dict: CFDictionaryRef, key: *const c_void) -> bool;
+    unsafe fn CFDictionaryGetKeysAndValues(dict: CFDictionaryRef, keys: *mut *const c_void, values: *mut *const c_void);
+    unsafe fn CFDictionaryCreate(
+        allocator: CFAllocatorRef,
+        keys: *const *const c_void,
+        values: *const *const c_void,
+        num_values: CFIndex,
+        key_callbacks: *const CFDictionaryKeyCallBacks,
+        value_callbacks: *const CFDictionaryValueCallBacks,
+    ) -> CFDictionaryRef;
+    unsafe fn CFDictionaryCreateMutable(
+        allocator: CFAllocatorRef,
+        capacity: CFIndex,
+        key_callbacks: *const CFDictionaryKeyCallBacks,
+        value_callbacks: *const CFDictionaryValueCallBacks,
+    ) -> CFMutableDictionaryRef;
+    unsafe fn CFDictionarySetValue(dict: CFMutableDictionaryRef, key: *const c_void, value: *const c_void);
+    unsafe fn CFDictionaryRemoveValue(dict: CFMutableDictionaryRef, key: *const c_void);
+    unsafe fn CFDictionaryRemoveAllValues(dict: CFMutableDictionaryRef);
+    pub unsafe static kCFTypeArrayCallBacks: CFArrayCallBacks;
+    pub unsafe static kCFTypeDictionaryKeyCallBacks: CFDictionaryKeyCallBacks;
+    pub unsafe static kCFTypeDictionaryValueCallBacks: CFDictionaryValueCallBacks;
+
+    // CFData //
     unsafe fn CFDataCreate(allocator: CFAllocatorRef, bytes: *const u8, length: CFIndex) -> CFDataRef;
+    unsafe fn CFDataCreateWithBytesNoCopy(
+        allocator: CFAllocatorRef,
+        bytes: *const u8,
+        length: CFIndex,
+        bytes_deallocator: CFAllocatorRef,
+    ) -> CFDataRef;
+    unsafe fn CFDataCreateMutable(allocator: CFAllocatorRef, capacity: CFIndex) -> CFMutableDataRef;
+    unsafe fn CFDataCreateMutableCopy(
+        allocator: CFAllocatorRef,
+        capacity: CFIndex,
+        data: CFDataRef,
+    ) -> CFMutableDataRef;
+    unsafe fn CFDataGetLength(data: CFDataRef) -> CFIndex;
+    unsafe fn CFDataGetBytePtr(data: CFDataRef) -> *const u8;
+    unsafe fn CFDataGetMutableBytePtr(data: CFMutableDataRef) -> *mut u8;
+    unsafe fn CFDataAppendBytes(data: CFMutableDataRef, bytes: *const u8, length: CFIndex);
+    unsafe fn CFDataSetLength(data: CFMutableDataRef, length: CFIndex);
+
+    // CFAllocator //
+    unsafe fn CFAllocatorCreate(allocator: CFAllocatorRef, context: *const CFAllocatorContext) -> CFAllocatorRef;
+    unsafe fn CFAllocatorAllocate(allocator: CFAllocatorRef, size: CFIndex, hint: CFOptionFlags) -> *mut c_void;
+    unsafe fn CFAllocatorDeallocate(allocator: CFAllocatorRef, ptr: *mut c_void);
+    /// The default allocator (a null reference).
+    pub unsafe static kCFAllocatorDefault: CFAllocatorRef;
+    /// The system default allocator.
+    pub unsafe static kCFAllocatorSystemDefault: CFAllocatorRef;
+    /// The allocator that uses `malloc`, `realloc` and `free`.
+    pub unsafe static kCFAllocatorMalloc: CFAllocatorRef;
+
+    // CFString //
+    unsafe fn CFStringCreateWithBytes(
+        alloc: CFAllocatorRef,
+        bytes: *const u8,
+        num_bytes: CFIndex,
+        encoding: CFStringEncoding,
+        is_external_representation: bool,
+    ) -> CFStringRef;
+    unsafe fn CFStringCreateWithBytesNoCopy(
+        alloc: CFAllocatorRef,
+        bytes: *const u8,
+        num_bytes: CFIndex,
+        encoding: CFStringEncoding,
+        is_external_representation: bool,
+        contents_deallocator: CFAllocatorRef,
+    ) -> CFStringRef;
+    unsafe fn CFStringGetLength(the_string: CFStringRef) -> CFIndex;
+    unsafe fn CFStringGetCharacterAtIndex(the_string: CFStringRef, idx: CFIndex) -> UniChar;
+    unsafe fn CFStringGetCharacters(the_string: CFStringRef, range: CFRange, buffer: *mut UniChar);
+    unsafe fn CFStringGetCharactersPtr(the_string: CFStringRef) -> *const UniChar;
+    unsafe fn CFStringGetCStringPtr(the_string: CFStringRef, encoding: CFStringEncoding) -> *const c_char;
+    unsafe fn CFStringGetBytes(
+        the_string: CFStringRef,
+        range: CFRange,
+        encoding: CFStringEncoding,
+        loss_byte: u8,
+        is_external_representation: bool,
+        buffer: *mut u8,
+        max_buf_len: CFIndex,
+        used_buf_len: *mut CFIndex,
+    ) -> CFIndex;
+    unsafe fn CFStringCompare(
+        the_string1: CFStringRef,
+        the_string2: CFStringRef,
+        compare_options: CFOptionFlags,
+    ) -> CFComparisonResult;
+
+    /// The allocator that does not allocate or deallocate memory.
+    pub unsafe static kCFAllocatorNull: CFAllocatorRef;
+
+    // CFNumber //
+    unsafe fn CFNumberCreate(allocator: CFAllocatorRef, the_type: CFIndex, value_ptr: *const c_void) -> CFNumberRef;
+    unsafe fn CFNumberGetType(number: CFNumberRef) -> CFIndex;
+    unsafe fn CFNumberGetValue(number: CFNumberRef, the_type: CFIndex, value_ptr: *mut c_void) -> bool;
+    unsafe fn CFNumberIsFloatType(number: CFNumberRef) -> bool;
+
+    // CFBoolean //
+    unsafe fn CFBooleanGetValue(boolean: CFBooleanRef) -> bool;
+    pub unsafe static kCFBooleanTrue: CFBooleanRef;
+    pub unsafe static kCFBooleanFalse: CFBooleanRef;
+
+    // CFDate //
+    unsafe fn CFDateGetTypeID() -> CFTypeID;
+    unsafe fn CFDateCreate(allocator: CFAllocatorRef, at: CFAbsoluteTime) -> CFDateRef;
+    unsafe fn CFDateGetAbsoluteTime(date: CFDateRef) -> CFAbsoluteTime;
+    unsafe fn CFDateGetTimeIntervalSinceDate(date: CFDateRef, other: CFDateRef) -> CFTimeInterval;
+
+    // CFRunLoop //
+    unsafe fn CFAbsoluteTimeGetCurrent() -> CFAbsoluteTime;
+    unsafe fn CFRunLoopGetTypeID() -> CFTypeID;
+    unsafe fn CFRunLoopGetCurrent() -> CFRunLoopRef;
+    unsafe fn CFRunLoopGetMain() -> CFRunLoopRef;
+    unsafe fn CFRunLoopRun();
+    unsafe fn CFRunLoopRunInMode(
+        mode: CFRunLoopMode,
+        seconds: CFTimeInterval,
+        return_after_source_handled: bool,
+    ) -> i32;
+    unsafe fn CFRunLoopStop(rl: CFRunLoopRef);
+    unsafe fn CFRunLoopWakeUp(rl: CFRunLoopRef);
+    unsafe fn CFRunLoopIsWaiting(rl: CFRunLoopRef) -> bool;
+    unsafe fn CFRunLoopCopyCurrentMode(rl: CFRunLoopRef) -> CFRunLoopMode;
+    unsafe fn CFRunLoopAddTimer(rl: CFRunLoopRef, timer: CFRunLoopTimerRef, mode: CFRunLoopMode);
+    unsafe fn CFRunLoopRemoveTimer(rl: CFRunLoopRef, timer: CFRunLoopTimerRef, mode: CFRunLoopMode);
+    unsafe fn CFRunLoopAddSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFRunLoopMode);
+    unsafe fn CFRunLoopRemoveSource(rl: CFRunLoopRef, source: CFRunLoopSourceRef, mode: CFRunLoopMode);
+    unsafe fn CFRunLoopAddObserver(rl: CFRunLoopRef, observer: CFRunLoopObserverRef, mode: CFRunLoopMode);
+    unsafe fn CFRunLoopRemoveObserver(rl: CFRunLoopRef, observer: CFRunLoopObserverRef, mode: CFRunLoopMode);
+    unsafe fn CFRunLoopTimerGetTypeID() -> CFTypeID;
+    unsafe fn CFRunLoopTimerCreate(
+        allocator: CFAllocatorRef,
+        fire_date: CFAbsoluteTime,
+        interval: CFTimeInterval,
+        flags: CFOptionFlags,
+        order: CFIndex,
+        callout: extern "C" fn(timer: CFRunLoopTimerRef, info: *mut c_void),
+        context: *mut CFRunLoopCallbackContext,
+    ) -> CFRunLoopTimerRef;
+    unsafe fn CFRunLoopTimerInvalidate(timer: CFRunLoopTimerRef);
+    unsafe fn CFRunLoopTimerIsValid(timer: CFRunLoopTimerRef) -> bool;
+    unsafe fn CFRunLoopTimerGetInterval(timer: CFRunLoopTimerRef) -> CFTimeInterval;
+    unsafe fn CFRunLoopTimerGetNextFireDate(timer: CFRunLoopTimerRef) -> CFAbsoluteTime;
+    unsafe fn CFRunLoopTimerSetNextFireDate(timer: CFRunLoopTimerRef, fire_date: CFAbsoluteTime);
+    unsafe fn CFRunLoopSourceGetTypeID() -> CFTypeID;
+    unsafe fn CFRunLoopSourceCreate(
+        allocator: CFAllocatorRef,
+        order: CFIndex,
+        context: *mut CFRunLoopSourceContext,
+    ) -> CFRunLoopSourceRef;
+    unsafe fn CFRunLoopSourceSignal(source: CFRunLoopSourceRef);
+    unsafe fn CFRunLoopSourceInvalidate(source: CFRunLoopSourceRef);
+    unsafe fn CFRunLoopSourceIsValid(source: CFRunLoopSourceRef) -> bool;
+    unsafe fn CFRunLoopObserverGetTypeID() -> CFTypeID;
+    unsafe fn CFRunLoopObserverCreate(
+        allocator: CFAllocatorRef,
+        activities: CFOptionFlags,
+        repeats: bool,
+        order: CFIndex,
+        callout: extern "C" fn(observer: CFRunLoopObserverRef, activity: CFOptionFlags, info: *mut c_void),
+        context: *mut CFRunLoopCallbackContext,
+    ) -> CFRunLoopObserverRef;
+    unsafe fn CFRunLoopObserverInvalidate(observer: CFRunLoopObserverRef);
+    unsafe fn CFRunLoopObserverIsValid(observer: CFRunLoopObserverRef) -> bool;
+    /// The mode to deal with objects while the run loop is idle.
+    pub unsafe static kCFRunLoopDefaultMode: CFRunLoopMode;
+    /// The pseudo-mode of the set of "common" modes.
+    pub unsafe static kCFRunLoopCommonModes: CFRunLoopMode;
+
+    // CFURL //
+    unsafe fn CFURLGetTypeID() -> CFTypeID;
+    unsafe fn CFURLCreateWithBytes(
+        allocator: CFAllocatorRef,
+        url_bytes: *const u8,
+        length: CFIndex,
+        encoding: CFStringEncoding,
+        base_url: CFURLRef,
+    ) -> CFURLRef;
+    #[cfg(feature = "std")]
+    unsafe fn CFURLCopyAbsoluteURL(relative_url: CFURLRef) -> CFURLRef;
+    unsafe fn CFURLGetString(an_url: CFURLRef) -> CFStringRef;
+    unsafe fn CFURLCopyScheme(an_url: CFURLRef) -> CFStringRef;
+    unsafe fn CFURLCopyHostName(an_url: CFURLRef) -> CFStringRef;
+    unsafe fn CFURLGetPortNumber(an_url: CFURLRef) -> i32;
+    unsafe fn CFURLCopyFileSystemPath(an_url: CFURLRef, path_style: CFURLPathStyle) -> CFStringRef;
+    unsafe fn CFURLHasDirectoryPath(an_url: CFURLRef) -> bool;
+    unsafe fn CFURLStartAccessingSecurityScopedResource(url: CFURLRef) -> bool;
+    unsafe fn CFURLStopAccessingSecurityScopedResource(url: CFURLRef);
+
+    // CFNotificationCenter //
+    unsafe fn CFNotificationCenterGetTypeID() -> CFTypeID;
+    unsafe fn CFNotificationCenterGetLocalCenter() -> CFNotificationCenterRef;
+    #[cfg(feature = "std")]
+    unsafe fn CFNotificationCenterAddObserver(
+        center: CFNotificationCenterRef,
+        observer: *const c_void,
+        call_back: extern "C" fn(
+            center: CFNotificationCenterRef,
+            observer: *mut c_void,
+            name: CFNotificationName,
+            object: *const c_void,
+            user_info: CFDictionaryRef,
+        ),
+        name: CFNotificationName,
+        object: *const c_void,
+        suspension_behavior: CFIndex,
+    );
+    #[cfg(feature = "std")]
+    unsafe fn CFNotificationCenterRemoveEveryObserver(center: CFNotificationCenterRef, observer: *const c_void);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NSNotFound, NSRange, NSUInteger};
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn cfrange_from_ranges() {
+        assert_eq!(CFRange::from(2..5), CFRange::new(2, 3));
+        assert_eq!(CFRange::from(5..3), CFRange::new(5, 0));
+        assert_eq!(CFRange::from(CFIndex::MIN..CFIndex::MAX).length, CFIndex::MAX);
+        assert_eq!(CFRange::from(2..=4), CFRange::new(2, 3));
+        assert_eq!(CFRange::from(5..=3), CFRange::new(5, 0));
+        assert_eq!(CFRange::from(0..=CFIndex::MAX), CFRange::new(0, CFIndex::MAX));
+        assert_eq!(CFRange::from(CFIndex::MIN..=CFIndex::MAX).length, CFIndex::MAX);
+        assert_eq!(CFRange::from(3..), CFRange::new(3, CFIndex::MAX));
+        assert_eq!(CFRange::from(..3), CFRange::new(0, 3));
+        assert_eq!(CFRange::from(..), CFRange::new(0, 0));
+    }
+
+    #[test]
+    fn cfrange_round_trip() {
+        for start in -4..8 {
+            for end in start..8 {
+                let r = CFRange::from(start..end);
+                assert_eq!(Range::<CFIndex>::from(r.clone()), start..end);
+                if end > start {
+                    assert_eq!(CFRange::from(start..=end - 1), r);
+                }
+                assert_eq!(r.to_range(), (start >= 0).then_some(start as usize..end as usize));
+            }
+        }
+
+        assert_eq!(CFRange::new(1, -2).to_range(), None);
+        assert_eq!(CFRange::new(3, CFIndex::MAX).end(), CFIndex::MAX);
+    }
+
+    #[test]
+    fn cfrange_from_nsrange() {
+        assert_eq!(CFRange::try_from(NSRange::new(2, 3)), Ok(CFRange::new(2, 3)));
+        assert_eq!(CFRange::try_from(NSRange::not_found()), Err(()));
+        assert_eq!(CFRange::try_from(NSRange::new(NSNotFound as NSUInteger, 3)), Err(()));
+        assert_eq!(CFRange::try_from(NSRange::new(0, NSUInteger::MAX)), Err(()));
+        assert_eq!(
+            CFRange::try_from(NSRange::new(CFIndex::MAX as NSUInteger - 1, 2)),
+            Err(())
+        );
+        assert_eq!(
+            CFRange::try_from(NSRange::new(CFIndex::MAX as NSUInteger - 2, 2)),
+            Ok(CFRange::new(CFIndex::MAX - 2, 2))
+        );
+    }
+
+    #[test]
+    fn absolute_time_arithmetic() {
+        const REF: i64 = ABSOLUTE_TIME_SINCE_1970_SECS;
+
+        assert_eq!(absolute_time_from_unix_time(REF, 0), 0.0);
+        assert_eq!(unix_time_from_absolute_time(0.0), Some((REF, 0)));
+        assert_eq!(unix_time_from_absolute_time(-0.0), Some((REF, 0)));
+        assert_eq!(absolute_time_from_unix_time(0, 0), -kCFAbsoluteTimeIntervalSince1970);
+
+        // before 2001
+        assert_eq!(absolute_time_from_unix_time(REF - 1, 750_000_000), -0.25);
+        assert_eq!(unix_time_from_absolute_time(-0.25), Some((REF - 1, 750_000_000)));
+        // before 1970
+        assert_eq!(absolute_time_from_unix_time(-2, 500_000_000), -(REF as f64) - 1.5);
+        assert_eq!(
+            unix_time_from_absolute_time(-(REF as f64) - 1.5),
+            Some((-2, 500_000_000))
+        );
+        assert_eq!(unix_time_from_absolute_time(-(REF as f64) - 2.0), Some((-2, 0)));
+
+        // below a nanosecond: rounds to the nearest, carrying into the seconds
+        assert_eq!(unix_time_from_absolute_time(1.0 - 1e-10), Some((REF + 1, 0)));
+        assert_eq!(unix_time_from_absolute_time(-1e-10), Some((REF, 0)));
+        assert_eq!(unix_time_from_absolute_time(-6e-10), Some((REF - 1, 999_999_999)));
+        assert_eq!(unix_time_from_absolute_time(0.5 + 4e-10), Some((REF, 500_000_000)));
+        assert_eq!(unix_time_from_absolute_time(0.5 + 6e-10), Some((REF, 500_000_001)));
+
+        for t in [
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            1e300,
+            -1e300,
+            i64::MAX as f64,
+            i64::MIN as f64,
+        ] {
+            assert_eq!(unix_time_from_absolute_time(t), None, "{t}");
+        }
+        // in range of `i64` but not after the offset to 1970
+        assert_eq!(unix_time_from_absolute_time((i64::MAX - 1024) as f64), None);
+        assert!(unix_time_from_absolute_time((i64::MIN + 1024) as f64).is_some());
+        // seconds out of the range of `f64` precision still keep their magnitude
+        assert_eq!(
+            absolute_time_from_unix_time(i64::MAX, 0),
+            (i64::MAX as i128 - REF as i128) as f64
+        );
+        assert_eq!(
+            absolute_time_from_unix_time(i64::MIN, 0),
+            (i64::MIN as i128 - REF as i128) as f64
+        );
+
+        for secs in [
+            -3_000_000_000i64,
+            -REF,
+            -1,
+            0,
+            1,
+            REF - 1,
+            REF,
+            700_000_000 + REF,
+            4_000_000_000,
+        ] {
+            for nanos in [0, 1_000, 499_999_000, 500_000_000, 999_999_000] {
+                let (s, n) = unix_time_from_absolute_time(absolute_time_from_unix_time(secs, nanos)).unwrap();
+                let diff = (s as i128 * 1_000_000_000 + n as i128) - (secs as i128 * 1_000_000_000 + nanos as i128);
+                assert!(diff.abs() < 1_000, "{secs}s {nanos}ns: {diff}ns");
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn absolute_time_conversions() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let reference = UNIX_EPOCH + Duration::from_secs(ABSOLUTE_TIME_SINCE_1970_SECS as u64);
+        assert_eq!(absolute_time_from_system_time(reference), 0.0);
+        assert_eq!(system_time_from_absolute_time(0.0), Some(reference));
+        assert_eq!(system_time_from_absolute_time(-0.0), Some(reference));
+
+        // before 1970
+        let t = UNIX_EPOCH - Duration::new(1, 500_000_000);
+        assert_eq!(
+            absolute_time_from_system_time(t),
+            -(ABSOLUTE_TIME_SINCE_1970_SECS as f64) - 1.5
+        );
+        assert_eq!(
+            system_time_from_absolute_time(-(ABSOLUTE_TIME_SINCE_1970_SECS as f64) - 1.5),
+            Some(t)
+        );
+        assert_eq!(
+            system_time_from_absolute_time(-0.25),
+            Some(reference - Duration::from_millis(250))
+        );
+
+        // nanoseconds rounding up to a whole second carry into the seconds
+        assert_eq!(
+            system_time_from_absolute_time(1.0 - 1e-10),
+            Some(reference + Duration::from_secs(1))
+        );
+        assert_eq!(system_time_from_absolute_time(-1e-10), Some(reference));
+        assert_eq!(
+            system_time_from_absolute_time(0.5 + 4e-10),
+            Some(reference + Duration::from_millis(500))
+        );
+
+        for t in [
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            1e300,
+            -1e300,
+            i64::MAX as f64,
+        ] {
+            assert_eq!(system_time_from_absolute_time(t), None, "{t}");
+        }
+        // in range of `i64` but not after the offset to 1970
+        assert_eq!(system_time_from_absolute_time((i64::MAX - 1024) as f64), None);
+
+        for secs in [
+            -3_000_000_000i64,
+            -ABSOLUTE_TIME_SINCE_1970_SECS,
+            -1,
+            0,
+            1,
+            700_000_000,
+            4_000_000_000,
+        ] {
+            for micros in [0, 1, 499_999, 500_000, 999_999] {
+                let d = Duration::new(secs.unsigned_abs(), 0);
+                let base = if secs < 0 { UNIX_EPOCH - d } else { UNIX_EPOCH + d };
+                let t = base + Duration::from_micros(micros);
+                let back = system_time_from_absolute_time(absolute_time_from_system_time(t)).unwrap();
+                let diff = back.duration_since(t).or_else(|e| Ok::<_, ()>(e.duration())).unwrap();
+                assert!(diff < Duration::from_micros(1), "{secs}s {micros}us: {diff:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn file_url_path_escapes() {
+        for (path, is_directory, url) in [
+            (&b"/"[..], true, "file:///"),
+            (b"/tmp/a b", false, "file:///tmp/a%20b"),
+            (b"/tmp/100%", false, "file:///tmp/100%25"),
+            (b"/tmp/#1", false, "file:///tmp/%231"),
+            (b"/tmp/a?b=c", false, "file:///tmp/a%3Fb=c"),
+            (b"/tmp/a;b", false, "file:///tmp/a%3Bb"),
+            (b"/tmp/\xff\xfe", false, "file:///tmp/%FF%FE"),
+            ("/tmp/\u{e9}".as_bytes(), false, "file:///tmp/%C3%A9"),
+            (b"/tmp/dir", true, "file:///tmp/dir/"),
+            (b"/tmp/dir/", true, "file:///tmp/dir/"),
+            (b"/tmp/dir/", false, "file:///tmp/dir/"),
+            (
+                b"/a-b_c.d~e/f:g@h!$&'()*+,=",
+                false,
+                "file:///a-b_c.d~e/f:g@h!$&'()*+,=",
+            ),
+        ] {
+            assert_eq!(file_url_from_path(path, is_directory).as_deref(), Some(url));
+            // the trailing slash of directories is not part of the file system representation
+            let expected = if path.len() > 1 {
+                path.strip_suffix(b"/").unwrap_or(path)
+            } else {
+                path
+            };
+            assert_eq!(path_from_file_url(url).as_deref(), Some(expected), "{url}");
+        }
+        assert_eq!(file_url_from_path(b"tmp/a", false), None);
+        assert_eq!(file_url_from_path(b"", true), None);
+
+        assert_eq!(
+            path_from_file_url("FILE://localhost/a%2fb%2F").as_deref(),
+            Some(&b"/a/b"[..])
+        );
+        assert_eq!(path_from_file_url("file:/tmp/a").as_deref(), Some(&b"/tmp/a"[..]));
+        assert_eq!(path_from_file_url("file:///tmp/a?x#y").as_deref(), Some(&b"/tmp/a"[..]));
+        assert_eq!(path_from_file_url("file:///tmp/a#%zz").as_deref(), Some(&b"/tmp/a"[..]));
+        for url in [
+            "https://example.com/a%20b",
+            "file://example.com/tmp",
+            "file://localhost",
+            "file:tmp/a",
+            "file:///tmp/%2",
+            "file:///tmp/%zz",
+            "file:///tmp/%00",
+            "/tmp/a",
+        ] {
+            assert_eq!(path_from_file_url(url), None, "{url}");
+        }
+    }
+
+    #[cfg(all(feature = "std", target_vendor = "apple"))]
+    #[test]
+    fn file_url_percent_encoding() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::Path;
+
+        for (path, is_directory, url) in [
+            (&b"/tmp/a b"[..], false, "file:///tmp/a%20b"),
+            (b"/tmp/100%", false, "file:///tmp/100%25"),
+            (b"/tmp/#1", false, "file:///tmp/%231"),
+            (b"/tmp/\xff\xfe", false, "file:///tmp/%FF%FE"),
+            (b"/tmp/dir", true, "file:///tmp/dir/"),
+            (b"/tmp/dir/", true, "file:///tmp/dir/"),
+        ] {
+            let u = CFURL::from_file_path(Path::new(OsStr::from_bytes(path)), is_directory).unwrap();
+            assert_eq!(u.string(), url);
+            assert_eq!(u.has_directory_path(), is_directory);
+            // the trailing slash of directories is not part of the file system representation
+            let expected = path.strip_suffix(b"/").unwrap_or(path);
+            assert_eq!(u.to_file_path().unwrap().as_os_str().as_bytes(), expected);
+            assert_eq!(
+                CFURL::new(url).unwrap().to_file_path().unwrap().as_os_str().as_bytes(),
+                expected
+            );
+        }
+
+        assert!(CFURL::new("file:///tmp/a b").is_none());
+        assert_eq!(CFURL::new("https://example.com/a%20b").unwrap().to_file_path(), None);
+        let long = alloc::format!("file:///{}", "a".repeat(PATH_MAX as usize));
+        assert_eq!(CFURL::new(&long).unwrap().to_file_path(), None);
+    }
 }
@@ -1,7 +1,8 @@
 //! Core Graphics
 
-use crate::{opt_pointer, CoreObject, CoreRetainedMutableObject, CoreRetainedObject};
+use crate::{opt_pointer, CFTypeID, CoreObject, CoreRetainedMutableObject, CoreRetainedObject};
 use libc::*;
+#[cfg(feature = "std")]
 use objc::{Encode, Encoding};
 
 /// A unique identifier for an attached display.
@@ -19,6 +20,7 @@ pub struct CGPoint {
     pub x: CGFloat,
     pub y: CGFloat,
 }
+#[cfg(feature = "std")]
 unsafe impl Encode for CGPoint {
     fn encode() -> Encoding {
         unsafe {
@@ -37,6 +39,7 @@ pub struct CGSize {
     pub width: CGFloat,
     pub height: CGFloat,
 }
+#[cfg(feature = "std")]
 unsafe impl Encode for CGSize {
     fn encode() -> Encoding {
         unsafe {
@@ -55,6 +58,7 @@ pub struct CGRect {
     pub origin: CGPoint,
     pub size: CGSize,
 }
+#[cfg(feature = "std")]
 unsafe impl Encode for CGRect {
     fn encode() -> Encoding {
         unsafe {
@@ -73,6 +77,7 @@ DefineCoreObject! {
 }
 /// A set of components that define a color, with a color space specifying how to interpret them.
 pub type CGColorRef = *mut CGColor;
+DeclareCFTypeID!(CGColor = CGColorGetTypeID);
 
 /// An index into a font table.
 pub type CGFontIndex = c_ushort;
@@ -89,6 +94,13 @@ pub struct CGAffineTransform {
     pub tx: CGFloat,
     pub ty: CGFloat,
 }
+#[cfg(feature = "std")]
+unsafe impl Encode for CGAffineTransform {
+    fn encode() -> Encoding {
+        let f = CGFloat::encode();
+        unsafe { Encoding::from_str(&format!("{{CGAffineTransform={}}}", f.as_str().repeat(6))) }
+    }
+}
 /// Identity scale, no rotation and transform
 impl Default for CGAffineTransform {
     #[inline(always)]
@@ -121,6 +133,7 @@ unsafe impl CoreObject for CGFont {
 }
 /// A set of character glyphs and layout information for drawing text.
 pub type CGFontRef = *mut CGFont;
+DeclareCFTypeID!(CGFont = CGFontGetTypeID);
 
 DefineOpaqueFFIObject! {
     pub struct CGPath;
@@ -140,6 +153,7 @@ unsafe impl CoreObject for CGPath {
 pub type CGPathRef = *const CGPath;
 /// A mutable graphics path: a mathematical description of shapes or lines to be drawn in a graphics context.
 pub type CGMutablePathRef = *mut CGPath;
+DeclareCFTypeID!(CGPath = CGPathGetTypeID);
 impl CGPath {
     /// Create an immutable path of a rectangle.
     #[inline(always)]
@@ -196,8 +210,11 @@ pub enum CGPathElementType {
 }
 
 pub type CGPathApplierFunction = extern "C" fn(info: *mut c_void, element: *const CGPathElement);
-#[link(name = "CoreGraphics", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "CoreGraphics", kind = "framework"))]
 unsafe extern "system" {
+    unsafe fn CGColorGetTypeID() -> CFTypeID;
+    unsafe fn CGFontGetTypeID() -> CFTypeID;
+    unsafe fn CGPathGetTypeID() -> CFTypeID;
     unsafe fn CGFontRelease(font: CGFontRef);
     unsafe fn CGFontRetain(font: CGFontRef) -> CGFontRef;
     unsafe fn CGPathCreateWithRect(rect: CGRect, transform: *const CGAffineTransform) -> CGPathRef;
@@ -1,10 +1,14 @@
 //! Core Text
 
+#[cfg(feature = "std")]
+use crate::NSFont;
 use crate::{
-    opt_pointer, opt_pointer_mut, CFArray, CFArrayRef, CFAttributedString, CFAttributedStringRef, CFData, CFDataRef,
-    CFDictionary, CFDictionaryRef, CFIndex, CFRange, CFStringRef, CGAffineTransform, CGFloat, CGFont, CGFontRef,
-    CGGlyph, CGPath, CGPathRef, CGPoint, CGRect, CGSize, CoreRetainedObject, NSFont, UniChar,
+    opt_pointer, opt_pointer_mut, CFArray, CFArrayOf, CFArrayRef, CFAttributedString, CFAttributedStringRef, CFData,
+    CFDataRef, CFDictionary, CFDictionaryOf, CFDictionaryRef, CFIndex, CFRange, CFString, CFStringRef, CFType,
+    CFTypeID, CGAffineTransform, CGFloat, CGFont, CGFontRef, CGGlyph, CGPath, CGPathRef, CGPoint, CGRect, CGSize,
+    CoreRetainedObject, UniChar, Utf16Index,
 };
+use alloc::vec::Vec;
 use core::ptr::null_mut;
 use core::slice;
 
@@ -29,6 +33,8 @@ DefineCoreObject! {
 }
 /// A reference to a Core Text font object.
 pub type CTFontRef = *const CTFont;
+DeclareCFTypeID!(CTFont = CTFontGetTypeID);
+#[cfg(feature = "std")]
 TollfreeBridge!(NSFont = CTFont);
 impl CTFont {
     /// Creates a new font reference from an existing Core Graphics font reference.
@@ -105,6 +111,21 @@ impl CTFont {
         }
     }
 
+    /// Returns the glyphs for the characters of a string, one glyph per `char`.
+    ///
+    /// Fails if the font cannot encode any of the characters.
+    pub fn glyphs_for_str(&self, s: &str) -> Result<Vec<CGGlyph>, ()> {
+        let characters = s.encode_utf16().collect::<Vec<_>>();
+        let glyphs = self.glyphs_for_characters(&characters)?;
+
+        // glyphs are laid out per UTF-16 code unit: a surrogate pair has its glyph on the first unit
+        let index = Utf16Index::new(s);
+        s.char_indices()
+            .map(|(byte, _)| Some(glyphs[index.byte_to_utf16(byte)?]))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(())
+    }
+
     /// Creates a path for the specified glyph.
     #[inline(always)]
     pub fn create_path_for_glyph(
@@ -219,6 +240,7 @@ DefineCoreObject! {
 }
 /// A reference to a CTFontDescriptor object.
 pub type CTFontDescriptorRef = *const CTFontDescriptor;
+DeclareCFTypeID!(CTFontDescriptor = CTFontDescriptorGetTypeID);
 impl CTFontDescriptor {
     #[inline(always)]
     pub fn with_attributes(attributes: &CFDictionary) -> Result<CoreRetainedObject<Self>, ()> {
@@ -237,6 +259,7 @@ DefineCoreObject! {
 }
 /// A reference to a CTFramesetter object.
 pub type CTFramesetterRef = *const CTFramesetter;
+DeclareCFTypeID!(CTFramesetter = CTFramesetterGetTypeID);
 impl CTFramesetter {
     /// Creates an immutable framesetter object from an attributed string.
     #[inline(always)]
@@ -286,11 +309,12 @@ DefineCoreObject! {
 }
 /// A reference to a Core Text frame object.
 pub type CTFrameRef = *const CTFrame;
+DeclareCFTypeID!(CTFrame = CTFrameGetTypeID);
 impl CTFrame {
     /// Returns an array of lines stored in the frame.
     #[inline(always)]
-    pub fn lines(&self) -> Result<&CFArray, ()> {
-        unsafe { CTFrameGetLines(self).as_ref().ok_or(()) }
+    pub fn lines(&self) -> Result<&CFArrayOf<CTLine>, ()> {
+        unsafe { CTFrameGetLines(self).as_ref().map(|a| a.as_typed_unchecked()).ok_or(()) }
     }
 
     /// Copies a range of line origins for a frame.
@@ -315,11 +339,17 @@ DefineCoreObject! {
 }
 /// A reference to a line object.
 pub type CTLineRef = *const CTLine;
+DeclareCFTypeID!(CTLine = CTLineGetTypeID);
 impl CTLine {
     /// Returns the array of glyph runs that make up the line object.
     #[inline(always)]
-    pub fn runs(&self) -> Result<&CFArray, ()> {
-        unsafe { CTLineGetGlyphRuns(self).as_ref().ok_or(()) }
+    pub fn runs(&self) -> Result<&CFArrayOf<CTRun>, ()> {
+        unsafe {
+            CTLineGetGlyphRuns(self)
+                .as_ref()
+                .map(|a| a.as_typed_unchecked())
+                .ok_or(())
+        }
     }
 }
 
@@ -329,6 +359,7 @@ DefineCoreObject! {
 }
 /// A reference to a run object.
 pub type CTRunRef = *const CTRun;
+DeclareCFTypeID!(CTRun = CTRunGetTypeID);
 impl CTRun {
     /// Gets the glyph count for the run.
     #[inline(always)]
@@ -404,13 +435,24 @@ impl CTRun {
 
     /// Returns the attribute dictionary that was used to create the glyph run.
     #[inline(always)]
-    pub fn attributes(&self) -> Result<&CFDictionary, ()> {
-        unsafe { CTRunGetAttributes(self).as_ref().ok_or(()) }
+    pub fn attributes(&self) -> Result<&CFDictionaryOf<CFString, CFType>, ()> {
+        unsafe {
+            CTRunGetAttributes(self)
+                .as_ref()
+                .map(|d| d.as_typed_unchecked())
+                .ok_or(())
+        }
     }
 }
 
-#[link(name = "CoreText", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "CoreText", kind = "framework"))]
 unsafe extern "system" {
+    unsafe fn CTFontGetTypeID() -> CFTypeID;
+    unsafe fn CTFontDescriptorGetTypeID() -> CFTypeID;
+    unsafe fn CTFramesetterGetTypeID() -> CFTypeID;
+    unsafe fn CTFrameGetTypeID() -> CFTypeID;
+    unsafe fn CTLineGetTypeID() -> CFTypeID;
+    unsafe fn CTRunGetTypeID() -> CFTypeID;
     unsafe fn CTFontCreateWithGraphicsFont(
         graphicsFont: CGFontRef,
         size: CGFloat,
@@ -1,7 +1,7 @@
 //! Core Video
 #![allow(non_upper_case_globals)]
 
-use crate::{CGDirectDisplayID, CoreObject, CoreRetainedMutableObject};
+use crate::{CFTypeID, CGDirectDisplayID, CoreObject, CoreRetainedMutableObject};
 
 /// A Core Video error type return value.
 pub type CVReturn = i32;
@@ -70,6 +70,7 @@ unsafe impl CoreObject for CVDisplayLink {
 }
 /// A reference to a display link object.
 pub type CVDisplayLinkRef = *mut CVDisplayLink;
+DeclareCFTypeID!(CVDisplayLink = CVDisplayLinkGetTypeID);
 
 impl CVDisplayLink {
     /// Creates a display link capable of being used with all active displays.
@@ -145,7 +146,7 @@ impl CVDisplayLink {
     }
 }
 
-#[link(name = "QuartzCore", kind = "framework")]
+#[cfg_attr(target_vendor = "apple", link(name = "QuartzCore", kind = "framework"))]
 unsafe extern "system" {
     unsafe fn CVDisplayLinkCreateWithCGDisplay(
         displayID: CGDirectDisplayID,
@@ -162,4 +163,5 @@ unsafe extern "system" {
     unsafe fn CVDisplayLinkIsRunning(displayLink: CVDisplayLinkRef) -> bool;
     unsafe fn CVDisplayLinkRetain(displayLink: CVDisplayLinkRef) -> CVDisplayLinkRef;
     unsafe fn CVDisplayLinkRelease(displayLink: CVDisplayLinkRef);
+    unsafe fn CVDisplayLinkGetTypeID() -> CFTypeID;
 }
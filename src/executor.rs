@@ -0,0 +1,212 @@
+//! Run loop integration of [`LocalExecutor`], and futures for AppKit callbacks
+//!
+//! ```no_run
+//! appkit::spawn_local(async {
+//!     // runs interleaved with `NSApplication::run`
+//! });
+//! ```
+
+use crate::{
+    callback_channel, CFRunLoop, CFRunLoopSource, CFRunLoopWaker, CFString, CallbackReceiver, CocoaMutableObject,
+    CocoaObject, CoreRetainedObject, JoinHandle, LocalExecutor, NSMenuItem, NSNotificationName, NSObject, NSWindow,
+    Wakeup,
+};
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_ext::ObjcObject;
+use std::cell::OnceCell;
+use std::ffi::c_void;
+use std::future::Future;
+use std::rc::{Rc, Weak};
+use std::sync::OnceLock;
+
+impl Wakeup for CFRunLoopWaker {
+    #[inline(always)]
+    fn wake(&self) {
+        CFRunLoopWaker::wake(self)
+    }
+}
+
+/// An executor that runs its tasks from a source of a CFRunLoop.
+pub struct RunLoopExecutor {
+    executor: Rc<LocalExecutor<CFRunLoopWaker>>,
+    source: CoreRetainedObject<CFRunLoopSource>,
+}
+impl RunLoopExecutor {
+    /// Creates an executor whose tasks run while the current thread's run loop is running in the mode.
+    pub fn new(mode: &CFString) -> Option<Self> {
+        let run_loop = CFRunLoop::current();
+        let cell = Rc::new(OnceCell::<Weak<LocalExecutor<CFRunLoopWaker>>>::new());
+        let c = cell.clone();
+        // the source is only added to `run_loop`, which runs on this thread
+        let source = unsafe {
+            CFRunLoopSource::new_unchecked(0, move || {
+                if let Some(e) = c.get().and_then(Weak::upgrade) {
+                    e.run_ready();
+                }
+            })?
+        };
+        let executor = Rc::new(LocalExecutor::new(source.waker(&run_loop)));
+        let _ = cell.set(Rc::downgrade(&executor));
+        run_loop.add_source(&source, mode);
+
+        Some(Self { executor, source })
+    }
+
+    /// Spawns a future onto the executor.
+    #[inline(always)]
+    pub fn spawn<F: Future + 'static>(&self, future: F) -> JoinHandle<F::Output> {
+        self.executor.spawn(future)
+    }
+
+    /// Returns the underlying executor.
+    #[inline(always)]
+    pub fn executor(&self) -> &LocalExecutor<CFRunLoopWaker> {
+        &self.executor
+    }
+}
+impl Drop for RunLoopExecutor {
+    fn drop(&mut self) {
+        self.source.invalidate();
+    }
+}
+
+thread_local! {
+    static CURRENT: RunLoopExecutor = RunLoopExecutor::new(CFRunLoop::common_modes())
+        .expect("CFRunLoopSourceCreate failed");
+}
+
+/// Spawns a future onto the executor of the current thread's run loop.
+///
+/// The future is polled while the run loop runs in any of the common modes, e.g. in `NSApplication::run`.
+pub fn spawn_local<F: Future + 'static>(future: F) -> JoinHandle<F::Output> {
+    CURRENT.with(|e| e.spawn(future))
+}
+
+/// Runs the current thread's run loop until the future completes.
+pub fn block_on<F: Future + 'static>(future: F) -> F::Output {
+    let handle = spawn_local(future);
+    loop {
+        if let Some(r) = handle.take_output() {
+            return r;
+        }
+
+        CFRunLoop::run_in_mode(CFRunLoop::default_mode(), 1.0e10, true);
+    }
+}
+
+/// An Objective-C object which calls a closure when it receives `invoke:`, used as an action target or an observer.
+struct CallbackTarget {
+    obj: CocoaObject<NSObject>,
+    detach: Box<dyn FnOnce(&Object)>,
+}
+impl CallbackTarget {
+    fn class() -> &'static Class {
+        static CLASS: OnceLock<&'static Class> = OnceLock::new();
+
+        extern "C" fn invoke(this: &Object, _: Sel, _sender: *mut Object) {
+            let f = unsafe { *this.get_ivar::<*mut c_void>("callback") } as *const Box<dyn Fn()>;
+            if let Some(f) = unsafe { f.as_ref() } {
+                f();
+            }
+        }
+
+        CLASS.get_or_init(|| {
+            let Some(mut decl) = ClassDecl::new("AppKitRsCallbackTarget", class!(NSObject)) else {
+                // registered by another copy of this crate
+                return Class::get("AppKitRsCallbackTarget").expect("no callback target class");
+            };
+            decl.add_ivar::<*mut c_void>("callback");
+            unsafe { decl.add_method(sel!(invoke:), invoke as extern "C" fn(&Object, Sel, *mut Object)) };
+
+            decl.register()
+        })
+    }
+
+    /// Creates a target calling `callback`. `detach` is called before the target is released.
+    fn new(callback: impl Fn() + 'static, detach: impl FnOnce(&Object) + 'static) -> Result<Self, ()> {
+        let p: *mut Object = unsafe { msg_send![Self::class(), new] };
+        let obj = unsafe { CocoaObject::<NSObject>::from_retained_id(p).ok_or(())? };
+        let f: Box<Box<dyn Fn()>> = Box::new(Box::new(callback));
+        unsafe { (*p).set_ivar::<*mut c_void>("callback", Box::into_raw(f) as _) };
+
+        Ok(Self {
+            obj,
+            detach: Box::new(detach),
+        })
+    }
+
+    #[inline(always)]
+    fn id(&self) -> &Object {
+        self.obj.as_id()
+    }
+}
+impl Drop for CallbackTarget {
+    fn drop(&mut self) {
+        let detach = std::mem::replace(&mut self.detach, Box::new(|_| {}));
+        detach(self.obj.as_id());
+
+        let p = self.obj.id() as *mut Object;
+        unsafe {
+            let f = *(*p).get_ivar::<*mut c_void>("callback") as *mut Box<dyn Fn()>;
+            (*p).set_ivar::<*mut c_void>("callback", std::ptr::null_mut());
+            drop(Box::from_raw(f));
+        }
+    }
+}
+
+/// Returns a receiver of the actions of the menu item. The target and action of the item are replaced.
+///
+/// The item sends to the receiver until the receiver is dropped, which resets the target and action of the item.
+pub fn menu_actions(item: &mut NSMenuItem) -> Result<CallbackReceiver<()>, ()> {
+    let (tx, mut rx) = callback_channel();
+    let retained = CocoaMutableObject::retain(item);
+    let target = CallbackTarget::new(
+        move || tx.send(()),
+        move |t| {
+            let current: *mut Object = unsafe { msg_send![&*retained, target] };
+            if std::ptr::eq(current, t) {
+                let _: () = unsafe { msg_send![&*retained, setTarget: std::ptr::null_mut::<Object>()] };
+                let _: () = unsafe { msg_send![&*retained, setAction: std::mem::zeroed::<Sel>()] };
+            }
+        },
+    )?;
+    item.set_target(target.id()).set_action(sel!(invoke:));
+    rx.keep_alive(target);
+
+    Ok(rx)
+}
+
+#[cfg_attr(target_vendor = "apple", link(name = "AppKit", kind = "framework"))]
+unsafe extern "system" {
+    static NSWindowWillCloseNotification: NSNotificationName;
+}
+
+/// Returns a receiver that gets a value when the window is about to close.
+///
+/// The receiver is a future itself, so the close can be waited for with `window_will_close(&w)?.await`.
+/// Must be used on the main thread.
+pub fn window_will_close(window: &NSWindow) -> Result<CallbackReceiver<()>, ()> {
+    let (tx, mut rx) = callback_channel();
+    let target = CallbackTarget::new(
+        move || tx.send(()),
+        |t| unsafe {
+            let center: *mut Object = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let _: () = msg_send![center, removeObserver: t];
+        },
+    )?;
+    unsafe {
+        let center: *mut Object = msg_send![class!(NSNotificationCenter), defaultCenter];
+        let _: () = msg_send![
+            center,
+            addObserver: target.id()
+            selector: sel!(invoke:)
+            name: NSWindowWillCloseNotification
+            object: window
+        ];
+    }
+    rx.keep_alive(target);
+
+    Ok(rx)
+}
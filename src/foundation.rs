@@ -1,11 +1,19 @@
 //! Foundation APIs
 
-use objc::{class, msg_send, runtime::*, sel, sel_impl};
+use bitflags::bitflags;
+use objc::{class, msg_send, runtime::*, sel, sel_impl, Encode};
 use objc_ext::ObjcObject;
-use std::ffi::CStr;
+use std::borrow::Cow;
+use std::ffi::{c_void, CStr, CString};
 use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::atomic::{AtomicPtr, Ordering};
 
-use crate::{CocoaMutableObject, CocoaObject, NSInteger, NSObject, NSUInteger};
+use crate::{
+    CFBoolean, CFData, CFNotificationCenter, CFNotificationObserver, CFNumber, CFNumberPrimitive, CFString, CFType,
+    CGAffineTransform, CGPoint, CGRect, CGSize, CocoaMutableObject, CocoaObject, NSInteger, NSNotFound, NSObject,
+    NSRange, NSUInteger, UniChar, CFURL,
+};
 
 objc_ext::DefineObjcObjectWrapper! {
     /// A static, plain-text Unicode string object.
@@ -39,31 +47,576 @@ impl NSString {
         unsafe { CStr::from_ptr(msg_send![self, UTF8String]) }
     }
 
+    /// Returns the contents through the autoreleased `UTF8String` buffer.
+    ///
+    /// Panics if the string contains unpaired surrogates.
     #[inline]
+    #[deprecated(note = "borrows an autoreleased buffer and panics on unpaired surrogates; use `to_string_lossy`")]
     pub fn to_str(&self) -> &str {
         self.to_cstr().to_str().unwrap()
     }
+
+    /// Returns the contents as a Rust string, borrowing the internal buffer when it is stored as UTF-8.
+    ///
+    /// Unpaired surrogates are replaced with U+FFFD.
+    #[inline(always)]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        self.as_cfstring().to_str()
+    }
+
+    #[inline(always)]
+    fn as_cfstring(&self) -> &CFString {
+        self.as_ref()
+    }
+
+    /// Returns the number of UTF-16 code units in the string.
+    #[inline(always)]
+    pub fn len_utf16(&self) -> NSUInteger {
+        unsafe { msg_send![self, length] }
+    }
+
+    /// Returns true if the string has no characters.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len_utf16() == 0
+    }
+
+    /// Returns the UTF-16 code unit at a given index, or `None` if the index is out of bounds.
+    #[inline]
+    pub fn character_at(&self, index: NSUInteger) -> Option<UniChar> {
+        if index >= self.len_utf16() {
+            return None;
+        }
+
+        Some(unsafe { msg_send![self, characterAtIndex: index] })
+    }
+
+    /// Returns the UTF-16 code units of the string, borrowing the internal buffer when it is stored as UTF-16.
+    #[inline(always)]
+    pub fn to_utf16(&self) -> Cow<'_, [UniChar]> {
+        self.as_cfstring().to_utf16()
+    }
+
+    /// Returns a new string containing the characters in a given range (in UTF-16 code units).
+    ///
+    /// Returns `None` if the range is out of bounds.
+    #[inline]
+    pub fn substring(&self, range: NSRange) -> Option<CocoaObject<Self>> {
+        if range.location.checked_add(range.length)? > self.len_utf16() {
+            return None;
+        }
+
+        let p: *mut Self = unsafe { msg_send![self, substringWithRange: range] };
+        // the substring is autoreleased
+        unsafe { p.as_ref() }.map(CocoaObject::retain)
+    }
+
+    /// Finds the first occurrence of a string, returning its range in UTF-16 code units.
+    #[inline]
+    pub fn range_of(&self, s: &NSString) -> Option<NSRange> {
+        let r: NSRange = unsafe { msg_send![self, rangeOfString: s] };
+
+        if r.is_not_found() {
+            None
+        } else {
+            Some(r)
+        }
+    }
+
+    /// Returns true if the string contains another string.
+    #[inline(always)]
+    pub fn contains(&self, s: &NSString) -> bool {
+        self.range_of(s).is_some()
+    }
+
+    /// Compares the string with another string by literal UTF-16 code units.
+    #[inline(always)]
+    pub fn compare(&self, other: &NSString) -> core::cmp::Ordering {
+        let r: NSInteger = unsafe { msg_send![self, compare: other] };
+        r.cmp(&0)
+    }
+
+    /// Compares the string with another string, ignoring case.
+    #[inline(always)]
+    pub fn case_insensitive_compare(&self, other: &NSString) -> core::cmp::Ordering {
+        let r: NSInteger = unsafe { msg_send![self, caseInsensitiveCompare: other] };
+        r.cmp(&0)
+    }
+}
+impl From<&str> for CocoaObject<NSString> {
+    /// Creates an NSString by copying a Rust string. Panics if the allocation fails.
+    #[inline]
+    fn from(s: &str) -> Self {
+        let bytes = s.as_bytes();
+
+        unsafe {
+            CocoaObject::from_retained_id(msg_send![NSString::alloc(), initWithBytes: bytes.as_ptr() as *const core::ffi::c_void length: bytes.len() encoding: 4 as NSUInteger])
+                .expect("NSString allocation failed")
+        }
+    }
+}
+impl PartialEq for NSString {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isEqualToString: other] };
+        r == YES
+    }
+}
+impl PartialEq<str> for NSString {
+    #[inline(always)]
+    fn eq(&self, other: &str) -> bool {
+        self.as_cfstring() == other
+    }
+}
+impl core::fmt::Display for NSString {
+    /// Copies the contents, so that no autoreleased buffer is borrowed.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Display::fmt(self.as_cfstring(), f)
+    }
+}
+impl core::fmt::Debug for NSString {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_cfstring(), f)
+    }
+}
+
+/// A lazily created constant NSString, used by [`ns_string!`].
+#[doc(hidden)]
+pub struct NSStringCache {
+    s: &'static str,
+    p: AtomicPtr<NSString>,
+}
+impl NSStringCache {
+    pub const fn new(s: &'static str) -> Self {
+        Self {
+            s,
+            p: AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+
+    /// Returns the cached string, creating it (without copying the literal) on the first call.
+    pub fn get(&self) -> &'static NSString {
+        let p = self.p.load(Ordering::Acquire);
+        if !p.is_null() {
+            return unsafe { &*p };
+        }
+
+        let s = CFString::from_static(self.s).expect("CFStringCreateWithBytesNoCopy failed");
+        let new = s.as_ptr() as *mut NSString;
+        match self
+            .p
+            .compare_exchange(core::ptr::null_mut(), new, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => {
+                // the cache owns the string forever
+                core::mem::forget(s);
+                unsafe { &*new }
+            }
+            Err(existing) => unsafe { &*existing },
+        }
+    }
+}
+
+/// Creates a constant `&'static NSString` from a string literal.
+///
+/// The string is created once per call site and refers the literal without copying.
+#[macro_export]
+macro_rules! ns_string {
+    ($s: literal) => {{
+        static CACHE: $crate::NSStringCache = $crate::NSStringCache::new($s);
+        CACHE.get()
+    }};
 }
 
+/// Plain data types that can be read back from an NSValue.
+///
+/// # Safety
+/// Every bit pattern of the size of the type must be a valid value of the type, as [`NSValue::get`] copies the
+/// bytes stored with a matching type encoding without further checks. References, `bool` and enums do not qualify.
+pub unsafe trait NSValueData: Encode {}
+unsafe impl NSValueData for i8 {}
+unsafe impl NSValueData for i16 {}
+unsafe impl NSValueData for i32 {}
+unsafe impl NSValueData for i64 {}
+unsafe impl NSValueData for isize {}
+unsafe impl NSValueData for u8 {}
+unsafe impl NSValueData for u16 {}
+unsafe impl NSValueData for u32 {}
+unsafe impl NSValueData for u64 {}
+unsafe impl NSValueData for usize {}
+unsafe impl NSValueData for f32 {}
+unsafe impl NSValueData for f64 {}
+unsafe impl NSValueData for CGPoint {}
+unsafe impl NSValueData for CGSize {}
+unsafe impl NSValueData for CGRect {}
+unsafe impl NSValueData for CGAffineTransform {}
+unsafe impl NSValueData for NSRange {}
+
 objc_ext::DefineObjcObjectWrapper! {
+    /// A simple container for a single C or Objective-C data item.
     pub NSValue : NSObject;
 }
+unsafe impl NSCopying for NSValue {}
+impl NSValue {
+    /// Creates a value object containing a copy of the value, tagged with the Objective-C type encoding of `T`.
+    pub fn from_value<T: Encode>(v: &T) -> Result<CocoaObject<Self>, ()> {
+        let ty = CString::new(T::encode().as_str()).map_err(drop)?;
+        let (p, ty) = (v as *const T as *const c_void, ty.as_ptr());
+
+        unsafe {
+            let o: *mut Object = msg_send![class!(NSValue), alloc];
+            CocoaObject::from_retained_id(msg_send![o, initWithBytes: p objCType: ty]).ok_or(())
+        }
+    }
+
+    /// Creates a value object containing a rectangle.
+    #[inline(always)]
+    pub fn from_rect(r: &CGRect) -> Result<CocoaObject<Self>, ()> {
+        Self::from_value(r)
+    }
+
+    /// Creates a value object containing a point.
+    #[inline(always)]
+    pub fn from_point(p: &CGPoint) -> Result<CocoaObject<Self>, ()> {
+        Self::from_value(p)
+    }
+
+    /// Creates a value object containing a size.
+    #[inline(always)]
+    pub fn from_size(s: &CGSize) -> Result<CocoaObject<Self>, ()> {
+        Self::from_value(s)
+    }
+
+    /// Creates a value object containing a range.
+    #[inline(always)]
+    pub fn from_range(r: &NSRange) -> Result<CocoaObject<Self>, ()> {
+        Self::from_value(r)
+    }
+
+    /// Creates a value object containing an affine transform.
+    #[inline(always)]
+    pub fn from_affine_transform(t: &CGAffineTransform) -> Result<CocoaObject<Self>, ()> {
+        Self::from_value(t)
+    }
+
+    /// Returns a C string containing the Objective-C type of the stored value.
+    #[inline(always)]
+    pub fn objc_type(&self) -> &CStr {
+        unsafe { CStr::from_ptr(msg_send![self, objCType]) }
+    }
+
+    /// Returns true if the stored value has the Objective-C type encoding of `T`.
+    #[inline]
+    pub fn is<T: Encode>(&self) -> bool {
+        self.objc_type().to_bytes() == T::encode().as_str().as_bytes()
+    }
+
+    /// Returns a copy of the stored value, or `None` if the stored type is not the type encoding of `T`.
+    pub fn get<T: NSValueData>(&self) -> Option<T> {
+        if !self.is::<T>() {
+            return None;
+        }
+
+        let mut v = core::mem::MaybeUninit::<T>::uninit();
+        let (p, size) = (v.as_mut_ptr() as *mut c_void, core::mem::size_of::<T>() as NSUInteger);
+        unsafe {
+            let _: () = msg_send![self, getValue: p size: size];
+            Some(v.assume_init())
+        }
+    }
+}
 
 objc_ext::DefineObjcObjectWrapper! {
     /// An object wrapper for primitive scalar numeric values.
     pub NSNumber : NSValue;
 }
 impl NSNumber {
+    #[inline(always)]
+    fn alloc() -> *mut Object {
+        unsafe { msg_send![class!(NSNumber), alloc] }
+    }
+
+    /// Creates an NSNumber object containing a boolean value.
+    #[inline(always)]
+    pub fn from_bool(v: bool) -> Result<CocoaObject<Self>, ()> {
+        let v = if v { YES } else { NO };
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithBool: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `char` value.
+    #[inline(always)]
+    pub fn from_i8(v: i8) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithChar: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing an `unsigned char` value.
+    #[inline(always)]
+    pub fn from_u8(v: u8) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithUnsignedChar: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `short` value.
+    #[inline(always)]
+    pub fn from_i16(v: i16) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithShort: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing an `unsigned short` value.
+    #[inline(always)]
+    pub fn from_u16(v: u16) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithUnsignedShort: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing an `int` value.
+    #[inline(always)]
+    pub fn from_i32(v: i32) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithInt: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing an `unsigned int` value.
+    #[inline(always)]
+    pub fn from_u32(v: u32) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithUnsignedInt: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `long long` value.
+    #[inline(always)]
+    pub fn from_i64(v: i64) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithLongLong: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing an `unsigned long long` value.
+    #[inline(always)]
+    pub fn from_u64(v: u64) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithUnsignedLongLong: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `float` value.
+    #[inline(always)]
+    pub fn from_f32(v: f32) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithFloat: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `double` value.
+    #[inline(always)]
+    pub fn from_f64(v: f64) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithDouble: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `NSInteger` value.
+    #[inline(always)]
+    pub fn from_integer(v: NSInteger) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithInteger: v]).ok_or(()) }
+    }
+
+    /// Creates an NSNumber object containing a `NSUInteger` value.
+    #[inline(always)]
+    pub fn from_unsigned_integer(v: NSUInteger) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithUnsignedInteger: v]).ok_or(()) }
+    }
+
     /// Creates and returns an NSNumber object containing a given value, treating it as a `float`.
     #[inline(always)]
     pub fn from_float(v: core::ffi::c_float) -> Result<CocoaObject<Self>, ()> {
-        unsafe { CocoaObject::from_retained_id(msg_send![class!(NSNumber), numberWithFloat: v]).ok_or(()) }
+        Self::from_f32(v)
     }
 
     /// Creates and returns an NSNumber object containing a given value, treating it as an `unsigned int`.
     #[inline(always)]
     pub fn from_uint(v: core::ffi::c_uint) -> Result<CocoaObject<Self>, ()> {
-        unsafe { CocoaObject::from_retained_id(msg_send![class!(NSNumber), numberWithUnsignedInt: v]).ok_or(()) }
+        Self::from_u32(v)
+    }
+
+    /// Returns the value as a boolean (true if the value is not zero).
+    #[inline(always)]
+    pub fn to_bool(&self) -> bool {
+        let b: BOOL = unsafe { msg_send![self, boolValue] };
+        b == YES
+    }
+
+    /// Returns the value converted to a `char`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn i8_value(&self) -> i8 {
+        unsafe { msg_send![self, charValue] }
+    }
+
+    /// Returns the value converted to an `unsigned char`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn u8_value(&self) -> u8 {
+        unsafe { msg_send![self, unsignedCharValue] }
+    }
+
+    /// Returns the value converted to a `short`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn i16_value(&self) -> i16 {
+        unsafe { msg_send![self, shortValue] }
+    }
+
+    /// Returns the value converted to an `unsigned short`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn u16_value(&self) -> u16 {
+        unsafe { msg_send![self, unsignedShortValue] }
+    }
+
+    /// Returns the value converted to an `int`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn i32_value(&self) -> i32 {
+        unsafe { msg_send![self, intValue] }
+    }
+
+    /// Returns the value converted to an `unsigned int`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn u32_value(&self) -> u32 {
+        unsafe { msg_send![self, unsignedIntValue] }
+    }
+
+    /// Returns the value converted to a `long long`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn i64_value(&self) -> i64 {
+        unsafe { msg_send![self, longLongValue] }
+    }
+
+    /// Returns the value converted to an `unsigned long long`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn u64_value(&self) -> u64 {
+        unsafe { msg_send![self, unsignedLongLongValue] }
+    }
+
+    /// Returns the value converted to a `float`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn f32_value(&self) -> f32 {
+        unsafe { msg_send![self, floatValue] }
+    }
+
+    /// Returns the value converted to a `double`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn f64_value(&self) -> f64 {
+        unsafe { msg_send![self, doubleValue] }
+    }
+
+    /// Returns the value converted to a `NSInteger`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn integer_value(&self) -> NSInteger {
+        unsafe { msg_send![self, integerValue] }
+    }
+
+    /// Returns the value converted to a `NSUInteger`, truncating or rounding as C casts do.
+    #[inline(always)]
+    pub fn unsigned_integer_value(&self) -> NSUInteger {
+        unsafe { msg_send![self, unsignedIntegerValue] }
+    }
+
+    /// Returns the value converted to a primitive type, or `None` if the conversion was lossy or the number is a
+    /// boolean.
+    #[inline(always)]
+    pub fn get<T: CFNumberPrimitive>(&self) -> Option<T> {
+        self.as_cfnumber()?.get()
+    }
+
+    /// Returns the value as an `u64`, or `None` if the value is negative, fractional or too large.
+    #[inline]
+    pub fn get_u64(&self) -> Option<u64> {
+        match self.value() {
+            NSNumberValue::U64(v) => Some(v),
+            NSNumberValue::F32(_) | NSNumberValue::F64(_) => {
+                let v = self.f64_value();
+                if v >= 0.0 && v < u64::MAX as f64 && v == (v as u64) as f64 {
+                    Some(v as u64)
+                } else {
+                    None
+                }
+            }
+            v => v.as_i64().and_then(|v| u64::try_from(v).ok()),
+        }
+    }
+
+    /// Returns the object as a CFNumber, or `None` if the object is a boolean.
+    #[inline(always)]
+    fn as_cfnumber(&self) -> Option<&CFNumber> {
+        unsafe { &*(self as *const Self as *const CFType) }.downcast()
+    }
+
+    /// Returns the stored value, tagged with the type reported by `objCType`.
+    ///
+    /// Cocoa may store values in a wider type than the one used on creation (e.g. unsigned values are usually
+    /// reported as the next wider signed type). Boolean numbers (`@YES`, `kCFBooleanTrue`) are reported as `Bool`.
+    pub fn value(&self) -> NSNumberValue {
+        if unsafe { &*(self as *const Self as *const CFType) }.is::<CFBoolean>() {
+            return NSNumberValue::Bool(self.to_bool());
+        }
+
+        match self.objc_type().to_bytes() {
+            b"B" => NSNumberValue::Bool(self.to_bool()),
+            b"c" => NSNumberValue::I8(self.i8_value()),
+            b"C" => NSNumberValue::U8(self.u8_value()),
+            b"s" => NSNumberValue::I16(self.i16_value()),
+            b"S" => NSNumberValue::U16(self.u16_value()),
+            b"i" => NSNumberValue::I32(self.i32_value()),
+            b"I" => NSNumberValue::U32(self.u32_value()),
+            b"l" if core::mem::size_of::<core::ffi::c_long>() == 4 => NSNumberValue::I32(self.i32_value()),
+            b"L" if core::mem::size_of::<core::ffi::c_ulong>() == 4 => NSNumberValue::U32(self.u32_value()),
+            b"l" | b"q" => NSNumberValue::I64(self.i64_value()),
+            b"L" | b"Q" => NSNumberValue::U64(self.u64_value()),
+            b"f" => NSNumberValue::F32(self.f32_value()),
+            _ => NSNumberValue::F64(self.f64_value()),
+        }
+    }
+}
+impl PartialEq for NSNumber {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isEqualToNumber: other] };
+        r == YES
+    }
+}
+
+/// A value stored in an NSNumber, tagged with its Objective-C type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NSNumberValue {
+    Bool(bool),
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+}
+impl NSNumberValue {
+    /// Returns the value as a 64-bit integer, or `None` if the value is a floating value or too large.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Bool(v) => Some(v as _),
+            Self::I8(v) => Some(v as _),
+            Self::U8(v) => Some(v as _),
+            Self::I16(v) => Some(v as _),
+            Self::U16(v) => Some(v as _),
+            Self::I32(v) => Some(v as _),
+            Self::U32(v) => Some(v as _),
+            Self::I64(v) => Some(v),
+            Self::U64(v) => i64::try_from(v).ok(),
+            Self::F32(_) | Self::F64(_) => None,
+        }
+    }
+
+    /// Returns the value as a 64-bit floating value.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::Bool(v) => v as u8 as _,
+            Self::I8(v) => v as _,
+            Self::U8(v) => v as _,
+            Self::I16(v) => v as _,
+            Self::U16(v) => v as _,
+            Self::I32(v) => v as _,
+            Self::U32(v) => v as _,
+            Self::I64(v) => v as _,
+            Self::U64(v) => v as _,
+            Self::F32(v) => v as _,
+            Self::F64(v) => v,
+        }
     }
 }
 
@@ -173,15 +726,18 @@ impl<KeyType: NSCopying, ObjectType: ObjcObject> NSMutableDictionary<KeyType, Ob
     #[inline(always)]
     pub fn with_capacity(cap: NSUInteger) -> Result<CocoaMutableObject<Self>, ()> {
         unsafe {
-            CocoaMutableObject::from_retained_id(msg_send![class!(NSMutableDictionary), dictionaryWithCapacity: cap])
-                .ok_or(())
+            let p: *mut Object = msg_send![class!(NSMutableDictionary), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, initWithCapacity: cap]).ok_or(())
         }
     }
 
     /// Creates a newly allocated mutable dictionary
     #[inline(always)]
     pub fn new() -> Result<CocoaMutableObject<Self>, ()> {
-        unsafe { CocoaMutableObject::from_retained_id(msg_send![class!(NSMutableDictionary), dictionary]).ok_or(()) }
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSMutableDictionary), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, init]).ok_or(())
+        }
     }
 
     /// Adds a given key-value pair to the dictionary.
@@ -202,6 +758,26 @@ impl<KeyType: NSCopying, ObjectType: ObjcObject> NSMutableDictionary<KeyType, Ob
         let _: () = unsafe { msg_send![self, removeAllObjects] };
     }
 }
+impl<'a, K: NSCopying + 'a, V: ObjcObject + 'a> Extend<(&'a K, &'a V)> for NSMutableDictionary<K, V> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.set(k, v);
+        }
+    }
+}
+impl<'a, K: NSCopying + 'a, V: ObjcObject + 'a> FromIterator<(&'a K, &'a V)>
+    for CocoaMutableObject<NSMutableDictionary<K, V>>
+{
+    /// Collects key-value pairs into a new mutable dictionary.
+    ///
+    /// # Panics
+    /// Panics if the dictionary could not be allocated.
+    fn from_iter<I: IntoIterator<Item = (&'a K, &'a V)>>(iter: I) -> Self {
+        let mut d = NSMutableDictionary::new().expect("Failed to allocate NSMutableDictionary");
+        d.extend(iter);
+        d
+    }
+}
 impl<KeyType: ObjcObject, ObjectType: ObjcObject> NSDictionary<KeyType, ObjectType> {
     /// The number of entries in the dictionary.
     #[inline(always)]
@@ -209,11 +785,50 @@ impl<KeyType: ObjcObject, ObjectType: ObjcObject> NSDictionary<KeyType, ObjectTy
         unsafe { msg_send![self, count] }
     }
 
-    /// Returns the value associated with a given key.
+    /// Returns true if the dictionary has no entries.
     #[inline(always)]
-    pub fn get(&self, key: &KeyType) -> &ObjectType {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value associated with a given key, or `None` if the key is not in the dictionary.
+    #[inline(always)]
+    pub fn get(&self, key: &KeyType) -> Option<&ObjectType> {
         let p: *mut Object = unsafe { msg_send![self, objectForKey: key] };
-        unsafe { &*(p as *const ObjectType) }
+        unsafe { (p as *const ObjectType).as_ref() }
+    }
+
+    /// Returns true if the dictionary has a value for the key.
+    #[inline(always)]
+    pub fn contains_key(&self, key: &KeyType) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator over the keys, in no particular order.
+    ///
+    /// # Panics
+    /// The iterator panics if the collection is mutated during the enumeration.
+    #[inline(always)]
+    pub fn keys(&self) -> NSFastEnumerator<'_, KeyType> {
+        unsafe { NSFastEnumerator::new(&self.0) }
+    }
+
+    /// Returns an iterator over the values, in the order of [`NSDictionary::keys`].
+    ///
+    /// # Panics
+    /// The iterator panics if the collection is mutated during the enumeration.
+    #[inline(always)]
+    pub fn values(&self) -> impl Iterator<Item = &ObjectType> + '_ {
+        self.keys().filter_map(move |k| self.get(k))
+    }
+
+    /// Returns an iterator over the key-value pairs, in no particular order.
+    ///
+    /// # Panics
+    /// The iterator panics if the collection is mutated during the enumeration.
+    #[inline(always)]
+    pub fn iter_pairs(&self) -> impl Iterator<Item = (&KeyType, &ObjectType)> + '_ {
+        self.keys().filter_map(move |k| Some((k, self.get(k)?)))
     }
 }
 
@@ -315,14 +930,18 @@ impl<ObjectType: ObjcObject> NSMutableArray<ObjectType> {
     /// Creates a newly allocated array.
     #[inline(always)]
     pub fn new() -> Result<CocoaMutableObject<Self>, ()> {
-        unsafe { CocoaMutableObject::from_retained_id(msg_send![class!(NSMutableArray), array]).ok_or(()) }
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSMutableArray), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, init]).ok_or(())
+        }
     }
 
     /// Creates and returns an `NSMutableArray` object with enough allocated memory to initially hold a given number of objects.
     #[inline(always)]
     pub fn with_capacity(cap: NSUInteger) -> Result<CocoaMutableObject<Self>, ()> {
         unsafe {
-            CocoaMutableObject::from_retained_id(msg_send![class!(NSMutableArray), arrayWithCapacity: cap]).ok_or(())
+            let p: *mut Object = msg_send![class!(NSMutableArray), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, initWithCapacity: cap]).ok_or(())
         }
     }
 
@@ -338,6 +957,18 @@ impl<ObjectType: ObjcObject> NSMutableArray<ObjectType> {
         let _: () = unsafe { msg_send![self, insertObject: object atIndex: index] };
     }
 
+    /// Removes the object at the index and returns it, or `None` if the index is out of bounds.
+    #[inline]
+    pub fn remove(&mut self, index: NSUInteger) -> Option<CocoaObject<ObjectType>>
+    where
+        ObjectType: objc::Message,
+    {
+        let o = CocoaObject::retain(self.get(index)?);
+        let _: () = unsafe { msg_send![self, removeObjectAtIndex: index] };
+
+        Some(o)
+    }
+
     /// Empties the array of all its elements.
     #[inline(always)]
     pub fn clear(&mut self) {
@@ -351,32 +982,1027 @@ impl<ObjectType: ObjcObject> NSArray<ObjectType> {
         unsafe { msg_send![self, count] }
     }
 
-    /// Returns the object located at the specified index.
+    /// Returns true if the array has no objects.
     #[inline(always)]
-    pub fn get(&self, index: NSUInteger) -> &ObjectType {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the object located at the specified index, or `None` if the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: NSUInteger) -> Option<&ObjectType> {
+        if index >= self.len() {
+            return None;
+        }
+
         let p: *mut Object = unsafe { msg_send![self, objectAtIndex: index] };
-        unsafe { &*(p as *const ObjectType) }
+        unsafe { (p as *const ObjectType).as_ref() }
     }
-}
 
-objc_ext::DefineObjcObjectWrapper! {
-    /// A representation of the code and resources stored in a bundle directory on disk.
-    pub NSBundle : NSObject;
-}
-impl NSBundle {
-    /// Returns the bundle object that contains the current executable.
+    /// Returns true if the array has an object equal (`isEqual:`) to the given object.
     #[inline(always)]
-    pub fn main() -> Result<&'static Self, ()> {
+    pub fn contains(&self, object: &ObjectType) -> bool {
+        let r: BOOL = unsafe { msg_send![self, containsObject: object] };
+        r == YES
+    }
+
+    /// Returns the lowest index whose object is equal (`isEqual:`) to the given object.
+    #[inline]
+    pub fn index_of(&self, object: &ObjectType) -> Option<NSUInteger> {
+        let i: NSUInteger = unsafe { msg_send![self, indexOfObject: object] };
+        if i == NSNotFound as NSUInteger {
+            None
+        } else {
+            Some(i)
+        }
+    }
+
+    /// Returns an iterator over the objects.
+    ///
+    /// # Panics
+    /// The iterator panics if the collection is mutated during the enumeration.
+    #[inline(always)]
+    pub fn iter(&self) -> NSFastEnumerator<'_, ObjectType> {
+        unsafe { NSFastEnumerator::new(&self.0) }
+    }
+}
+impl<'a, O: ObjcObject> IntoIterator for &'a NSArray<O> {
+    type Item = &'a O;
+    type IntoIter = NSFastEnumerator<'a, O>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, O: ObjcObject + 'a> Extend<&'a O> for NSMutableArray<O> {
+    fn extend<I: IntoIterator<Item = &'a O>>(&mut self, iter: I) {
+        for o in iter {
+            self.push(o);
+        }
+    }
+}
+impl<'a, O: ObjcObject + 'a> FromIterator<&'a O> for CocoaMutableObject<NSMutableArray<O>> {
+    /// Collects objects into a new mutable array.
+    ///
+    /// # Panics
+    /// Panics if the array could not be allocated.
+    fn from_iter<I: IntoIterator<Item = &'a O>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut a = NSMutableArray::with_capacity(iter.size_hint().0 as _).expect("Failed to allocate NSMutableArray");
+        a.extend(iter);
+        a
+    }
+}
+
+/// The state of an enumeration with `countByEnumeratingWithState:objects:count:`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct NSFastEnumerationState {
+    pub state: core::ffi::c_ulong,
+    pub items_ptr: *mut *mut Object,
+    pub mutations_ptr: *mut core::ffi::c_ulong,
+    pub extra: [core::ffi::c_ulong; 5],
+}
+
+/// An iterator over a collection conforming to `NSFastEnumeration`.
+///
+/// The collection yields its objects in batches, which are fetched either in place or into a buffer owned by the
+/// iterator.
+///
+/// # Panics
+/// Like `for ... in` loops in Objective-C, the iterator checks the mutation counter of the collection for each
+/// batch, and panics if the collection was mutated since the enumeration started.
+pub struct NSFastEnumerator<'a, T: ObjcObject> {
+    collection: &'a Object,
+    state: NSFastEnumerationState,
+    buf: [*mut Object; 16],
+    /// true if the current batch is in `buf` rather than in the collection.
+    buffered: bool,
+    pos: usize,
+    count: usize,
+    mutations: core::ffi::c_ulong,
+    _ph: PhantomData<&'a T>,
+}
+impl<'a, T: ObjcObject> NSFastEnumerator<'a, T> {
+    /// Starts enumerating a collection.
+    ///
+    /// # Safety
+    /// The collection must conform to `NSFastEnumeration` and enumerate objects of type `T`.
+    pub unsafe fn new(collection: &'a Object) -> Self {
+        Self {
+            collection,
+            state: NSFastEnumerationState {
+                state: 0,
+                items_ptr: core::ptr::null_mut(),
+                mutations_ptr: core::ptr::null_mut(),
+                extra: [0; 5],
+            },
+            buf: [core::ptr::null_mut(); 16],
+            buffered: false,
+            pos: 0,
+            count: 0,
+            mutations: 0,
+            _ph: PhantomData,
+        }
+    }
+
+    fn fetch(&mut self) -> bool {
+        let first = self.state.state == 0;
+        // the buffer may have moved with the iterator since the last batch
+        self.state.items_ptr = core::ptr::null_mut();
+        let state = &mut self.state as *mut NSFastEnumerationState;
+        let (objects, len) = (self.buf.as_mut_ptr(), self.buf.len() as NSUInteger);
+        let count: NSUInteger =
+            unsafe { msg_send![self.collection, countByEnumeratingWithState: state objects: objects count: len] };
+        if count == 0 {
+            return false;
+        }
+
+        let mutations = unsafe { self.state.mutations_ptr.as_ref().copied() }.unwrap_or(0);
+        if first {
+            self.mutations = mutations;
+        } else {
+            assert_eq!(
+                self.mutations, mutations,
+                "Collection was mutated while being enumerated"
+            );
+        }
+        self.buffered = self.state.items_ptr == self.buf.as_mut_ptr();
+        self.pos = 0;
+        self.count = count as _;
+
+        true
+    }
+}
+impl<'a, T: ObjcObject> Iterator for NSFastEnumerator<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.pos >= self.count && !self.fetch() {
+            return None;
+        }
+
+        let p = if self.buffered {
+            self.buf[self.pos]
+        } else {
+            unsafe { *self.state.items_ptr.add(self.pos) }
+        };
+        self.pos += 1;
+
+        unsafe { (p as *const T).as_ref() }
+    }
+}
+
+/// A static, unordered collection of unique objects.
+#[repr(C)]
+pub struct NSSet<ObjectType: ObjcObject>(Object, PhantomData<*mut ObjectType>);
+unsafe impl<O: ObjcObject> ObjcObject for NSSet<O> {
+    #[inline(always)]
+    fn as_id(&self) -> &Object {
+        &self.0
+    }
+
+    #[inline(always)]
+    fn as_id_mut(&mut self) -> &mut Object {
+        &mut self.0
+    }
+}
+unsafe impl<O: ObjcObject> objc::Message for NSSet<O> {
+    #[inline(always)]
+    unsafe fn send_message<A, R>(&self, sel: Sel, args: A) -> Result<R, objc::MessageError>
+    where
+        Self: Sized,
+        A: objc::MessageArguments,
+        R: std::any::Any,
+    {
+        self.0.send_message::<A, R>(sel, args)
+    }
+
+    #[inline(always)]
+    fn verify_message<A, R>(&self, sel: Sel) -> Result<(), objc::MessageError>
+    where
+        Self: Sized,
+        A: objc::EncodeArguments,
+        R: objc::Encode,
+    {
+        self.0.verify_message::<A, R>(sel)
+    }
+}
+impl<O: ObjcObject> core::ops::Deref for NSSet<O> {
+    type Target = NSObject;
+
+    #[inline(always)]
+    fn deref(&self) -> &NSObject {
+        unsafe { core::mem::transmute(self) }
+    }
+}
+unsafe impl<O: ObjcObject> NSCopying for NSSet<O> {}
+impl<ObjectType: ObjcObject> NSSet<ObjectType> {
+    /// Creates an empty set.
+    #[inline(always)]
+    pub fn new() -> Result<CocoaObject<Self>, ()> {
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSSet), alloc];
+            CocoaObject::from_retained_id(msg_send![p, init]).ok_or(())
+        }
+    }
+
+    /// The number of members in the set.
+    #[inline(always)]
+    pub fn len(&self) -> NSUInteger {
+        unsafe { msg_send![self, count] }
+    }
+
+    /// Returns true if the set has no members.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the set has a member equal (`isEqual:`) to the given object.
+    #[inline(always)]
+    pub fn contains(&self, object: &ObjectType) -> bool {
+        let r: BOOL = unsafe { msg_send![self, containsObject: object] };
+        r == YES
+    }
+
+    /// Returns the member equal (`isEqual:`) to the given object, if any.
+    #[inline(always)]
+    pub fn member(&self, object: &ObjectType) -> Option<&ObjectType> {
+        let p: *mut Object = unsafe { msg_send![self, member: object] };
+        unsafe { (p as *const ObjectType).as_ref() }
+    }
+
+    /// Returns one of the members of the set, or `None` if the set is empty.
+    #[inline(always)]
+    pub fn any(&self) -> Option<&ObjectType> {
+        let p: *mut Object = unsafe { msg_send![self, anyObject] };
+        unsafe { (p as *const ObjectType).as_ref() }
+    }
+
+    /// Returns true if every member of the set is also a member of the other set.
+    #[inline(always)]
+    pub fn is_subset(&self, other: &NSSet<ObjectType>) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isSubsetOfSet: other] };
+        r == YES
+    }
+
+    /// Returns true if at least one member of the set is also a member of the other set.
+    #[inline(always)]
+    pub fn intersects(&self, other: &NSSet<ObjectType>) -> bool {
+        let r: BOOL = unsafe { msg_send![self, intersectsSet: other] };
+        r == YES
+    }
+
+    /// Returns an iterator over the members, in no particular order.
+    ///
+    /// # Panics
+    /// The iterator panics if the collection is mutated during the enumeration.
+    #[inline(always)]
+    pub fn iter(&self) -> NSFastEnumerator<'_, ObjectType> {
+        unsafe { NSFastEnumerator::new(&self.0) }
+    }
+}
+impl<'a, O: ObjcObject> IntoIterator for &'a NSSet<O> {
+    type Item = &'a O;
+    type IntoIter = NSFastEnumerator<'a, O>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A dynamic, unordered collection of unique objects.
+#[repr(C)]
+pub struct NSMutableSet<ObjectType: ObjcObject>(Object, PhantomData<*mut ObjectType>);
+unsafe impl<O: ObjcObject> ObjcObject for NSMutableSet<O> {
+    #[inline(always)]
+    fn as_id(&self) -> &Object {
+        &self.0
+    }
+
+    #[inline(always)]
+    fn as_id_mut(&mut self) -> &mut Object {
+        &mut self.0
+    }
+}
+unsafe impl<O: ObjcObject> objc::Message for NSMutableSet<O> {
+    #[inline(always)]
+    unsafe fn send_message<A, R>(&self, sel: Sel, args: A) -> Result<R, objc::MessageError>
+    where
+        Self: Sized,
+        A: objc::MessageArguments,
+        R: std::any::Any,
+    {
+        self.0.send_message::<A, R>(sel, args)
+    }
+
+    #[inline(always)]
+    fn verify_message<A, R>(&self, sel: Sel) -> Result<(), objc::MessageError>
+    where
+        Self: Sized,
+        A: objc::EncodeArguments,
+        R: objc::Encode,
+    {
+        self.0.verify_message::<A, R>(sel)
+    }
+}
+impl<O: ObjcObject> core::ops::Deref for NSMutableSet<O> {
+    type Target = NSSet<O>;
+
+    #[inline(always)]
+    fn deref(&self) -> &NSSet<O> {
+        unsafe { core::mem::transmute(self) }
+    }
+}
+impl<O: ObjcObject> core::ops::DerefMut for NSMutableSet<O> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut NSSet<O> {
+        unsafe { core::mem::transmute(self) }
+    }
+}
+impl<ObjectType: ObjcObject> NSMutableSet<ObjectType> {
+    /// Creates an empty mutable set.
+    #[inline(always)]
+    pub fn new() -> Result<CocoaMutableObject<Self>, ()> {
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSMutableSet), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, init]).ok_or(())
+        }
+    }
+
+    /// Creates an empty mutable set with enough allocated memory to initially hold a given number of objects.
+    #[inline(always)]
+    pub fn with_capacity(cap: NSUInteger) -> Result<CocoaMutableObject<Self>, ()> {
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSMutableSet), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, initWithCapacity: cap]).ok_or(())
+        }
+    }
+
+    /// Adds an object to the set, if it is not already a member.
+    #[inline(always)]
+    pub fn insert(&mut self, object: &ObjectType) {
+        let _: () = unsafe { msg_send![self, addObject: object] };
+    }
+
+    /// Removes an object from the set.
+    #[inline(always)]
+    pub fn remove(&mut self, object: &ObjectType) {
+        let _: () = unsafe { msg_send![self, removeObject: object] };
+    }
+
+    /// Removes all members of the set.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        let _: () = unsafe { msg_send![self, removeAllObjects] };
+    }
+}
+impl<'a, O: ObjcObject + 'a> Extend<&'a O> for NSMutableSet<O> {
+    fn extend<I: IntoIterator<Item = &'a O>>(&mut self, iter: I) {
+        for o in iter {
+            self.insert(o);
+        }
+    }
+}
+impl<'a, O: ObjcObject + 'a> FromIterator<&'a O> for CocoaMutableObject<NSMutableSet<O>> {
+    /// Collects objects into a new mutable set.
+    ///
+    /// # Panics
+    /// Panics if the set could not be allocated.
+    fn from_iter<I: IntoIterator<Item = &'a O>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut s = NSMutableSet::with_capacity(iter.size_hint().0 as _).expect("Failed to allocate NSMutableSet");
+        s.extend(iter);
+        s
+    }
+}
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// An immutable collection of unique integer values that represent indexes in another collection.
+    pub NSIndexSet : NSObject;
+}
+unsafe impl NSCopying for NSIndexSet {}
+impl NSIndexSet {
+    /// Creates an empty index set.
+    #[inline(always)]
+    pub fn new() -> Result<CocoaObject<Self>, ()> {
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSIndexSet), alloc];
+            CocoaObject::from_retained_id(msg_send![p, init]).ok_or(())
+        }
+    }
+
+    /// Creates an index set with the indexes in a range.
+    #[inline(always)]
+    pub fn from_range(r: Range<NSUInteger>) -> Result<CocoaObject<Self>, ()> {
+        let r = NSRange::from(r);
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSIndexSet), alloc];
+            CocoaObject::from_retained_id(msg_send![p, initWithIndexesInRange: r]).ok_or(())
+        }
+    }
+
+    /// The number of indexes in the set.
+    #[inline(always)]
+    pub fn len(&self) -> NSUInteger {
+        unsafe { msg_send![self, count] }
+    }
+
+    /// Returns true if the set has no indexes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the set contains the index.
+    #[inline(always)]
+    pub fn contains(&self, index: NSUInteger) -> bool {
+        let r: BOOL = unsafe { msg_send![self, containsIndex: index] };
+        r == YES
+    }
+
+    /// Returns true if the set contains every index in the range.
+    #[inline(always)]
+    pub fn contains_range(&self, r: Range<NSUInteger>) -> bool {
+        let r = NSRange::from(r);
+        let r: BOOL = unsafe { msg_send![self, containsIndexesInRange: r] };
+        r == YES
+    }
+
+    /// Returns the first index in the set, or `None` if the set is empty.
+    #[inline(always)]
+    pub fn first(&self) -> Option<NSUInteger> {
+        Self::found(unsafe { msg_send![self, firstIndex] })
+    }
+
+    /// Returns the last index in the set, or `None` if the set is empty.
+    #[inline(always)]
+    pub fn last(&self) -> Option<NSUInteger> {
+        Self::found(unsafe { msg_send![self, lastIndex] })
+    }
+
+    /// Returns the smallest index in the set which is greater than the given index.
+    #[inline(always)]
+    pub fn next_after(&self, index: NSUInteger) -> Option<NSUInteger> {
+        Self::found(unsafe { msg_send![self, indexGreaterThanIndex: index] })
+    }
+
+    #[inline(always)]
+    fn found(index: NSUInteger) -> Option<NSUInteger> {
+        if index == NSNotFound as NSUInteger {
+            None
+        } else {
+            Some(index)
+        }
+    }
+
+    /// Returns an iterator over the indexes in ascending order.
+    #[inline(always)]
+    pub fn iter(&self) -> NSIndexSetIter<'_> {
+        NSIndexSetIter {
+            set: self,
+            next: self.first(),
+        }
+    }
+
+    /// Returns the indexes as a list of disjoint ranges in ascending order.
+    pub fn ranges(&self) -> Vec<Range<NSUInteger>> {
+        let mut ranges: Vec<Range<NSUInteger>> = Vec::new();
+        let mut buf = [0 as NSUInteger; 64];
+        let mut rest = NSRange::new(0, NSNotFound as NSUInteger);
+        loop {
+            let n: NSUInteger = unsafe {
+                msg_send![self, getIndexes: buf.as_mut_ptr() maxCount: buf.len() as NSUInteger inIndexRange: &mut rest as *mut NSRange]
+            };
+            for &i in &buf[..n as usize] {
+                match ranges.last_mut() {
+                    Some(r) if r.end == i => r.end = i + 1,
+                    _ => ranges.push(i..i + 1),
+                }
+            }
+            if (n as usize) < buf.len() {
+                return ranges;
+            }
+        }
+    }
+}
+impl<'a> IntoIterator for &'a NSIndexSet {
+    type Item = NSUInteger;
+    type IntoIter = NSIndexSetIter<'a>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the indexes of an `NSIndexSet`.
+pub struct NSIndexSetIter<'a> {
+    set: &'a NSIndexSet,
+    next: Option<NSUInteger>,
+}
+impl Iterator for NSIndexSetIter<'_> {
+    type Item = NSUInteger;
+
+    #[inline]
+    fn next(&mut self) -> Option<NSUInteger> {
+        let i = self.next?;
+        self.next = self.set.next_after(i);
+
+        Some(i)
+    }
+}
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// A mutable collection of unique integer values that represent indexes in another collection.
+    pub NSMutableIndexSet : NSIndexSet;
+}
+impl NSMutableIndexSet {
+    /// Creates an empty mutable index set.
+    #[inline(always)]
+    pub fn new() -> Result<CocoaMutableObject<Self>, ()> {
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSMutableIndexSet), alloc];
+            CocoaMutableObject::from_retained_id(msg_send![p, init]).ok_or(())
+        }
+    }
+
+    /// Adds an index to the set.
+    #[inline(always)]
+    pub fn insert(&mut self, index: NSUInteger) {
+        let _: () = unsafe { msg_send![self, addIndex: index] };
+    }
+
+    /// Adds the indexes in a range to the set.
+    #[inline(always)]
+    pub fn insert_range(&mut self, r: Range<NSUInteger>) {
+        let r = NSRange::from(r);
+        let _: () = unsafe { msg_send![self, addIndexesInRange: r] };
+    }
+
+    /// Removes an index from the set.
+    #[inline(always)]
+    pub fn remove(&mut self, index: NSUInteger) {
+        let _: () = unsafe { msg_send![self, removeIndex: index] };
+    }
+
+    /// Removes the indexes in a range from the set.
+    #[inline(always)]
+    pub fn remove_range(&mut self, r: Range<NSUInteger>) {
+        let r = NSRange::from(r);
+        let _: () = unsafe { msg_send![self, removeIndexesInRange: r] };
+    }
+
+    /// Removes all indexes from the set.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        let _: () = unsafe { msg_send![self, removeAllIndexes] };
+    }
+}
+impl Extend<NSUInteger> for NSMutableIndexSet {
+    fn extend<I: IntoIterator<Item = NSUInteger>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+impl Extend<Range<NSUInteger>> for NSMutableIndexSet {
+    fn extend<I: IntoIterator<Item = Range<NSUInteger>>>(&mut self, iter: I) {
+        for r in iter {
+            self.insert_range(r);
+        }
+    }
+}
+impl FromIterator<NSUInteger> for CocoaMutableObject<NSMutableIndexSet> {
+    /// Collects indexes into a new mutable index set.
+    ///
+    /// # Panics
+    /// Panics if the set could not be allocated.
+    fn from_iter<I: IntoIterator<Item = NSUInteger>>(iter: I) -> Self {
+        let mut s = NSMutableIndexSet::new().expect("Failed to allocate NSMutableIndexSet");
+        s.extend(iter);
+        s
+    }
+}
+impl FromIterator<Range<NSUInteger>> for CocoaMutableObject<NSMutableIndexSet> {
+    /// Collects ranges of indexes into a new mutable index set.
+    ///
+    /// # Panics
+    /// Panics if the set could not be allocated.
+    fn from_iter<I: IntoIterator<Item = Range<NSUInteger>>>(iter: I) -> Self {
+        let mut s = NSMutableIndexSet::new().expect("Failed to allocate NSMutableIndexSet");
+        s.extend(iter);
+        s
+    }
+}
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// A static byte buffer in memory.
+    pub NSData : NSObject;
+}
+unsafe impl NSCopying for NSData {}
+impl NSData {
+    #[inline(always)]
+    fn alloc() -> *mut Object {
+        unsafe { msg_send![class!(NSData), alloc] }
+    }
+
+    /// Creates an empty data object.
+    #[inline(always)]
+    pub fn new() -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), init]).ok_or(()) }
+    }
+
+    /// Creates a data object by copying the bytes.
+    #[inline(always)]
+    pub fn from_slice(bytes: &[u8]) -> Result<CocoaObject<Self>, ()> {
+        let (p, len) = (bytes.as_ptr() as *const c_void, bytes.len() as NSUInteger);
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithBytes: p length: len]).ok_or(()) }
+    }
+
+    /// Creates a data object that refers a static byte slice directly, without copying its contents.
+    #[inline(always)]
+    pub fn from_static(bytes: &'static [u8]) -> Result<CocoaObject<Self>, ()> {
+        let (p, len) = (bytes.as_ptr() as *mut c_void, bytes.len() as NSUInteger);
+        unsafe {
+            CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithBytesNoCopy: p length: len freeWhenDone: NO])
+                .ok_or(())
+        }
+    }
+
+    /// Creates a data object that takes ownership of the buffer without copying.
+    ///
+    /// The buffer is freed by the Rust global allocator when the data object is deallocated. The object is created
+    /// through the toll-free bridged `CFData`, as `initWithBytesNoCopy:length:deallocator:` requires a block.
+    #[inline(always)]
+    pub fn from_vec(bytes: Vec<u8>) -> Result<CocoaObject<Self>, ()> {
+        let d = CFData::from_vec(bytes).ok_or(())?;
+
+        Ok(CocoaObject::retain(AsRef::<NSData>::as_ref(&*d)))
+    }
+
+    /// The number of bytes contained by the data object.
+    #[inline(always)]
+    pub fn len(&self) -> NSUInteger {
+        unsafe { msg_send![self, length] }
+    }
+
+    /// Returns true if the data object contains no bytes.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bytes of the data object.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self.len();
+        let p: *const u8 = unsafe { msg_send![self, bytes] };
+        if p.is_null() || len == 0 {
+            return &[];
+        }
+
+        unsafe { core::slice::from_raw_parts(p, len as _) }
+    }
+}
+impl AsRef<[u8]> for NSData {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+impl PartialEq for NSData {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isEqualToData: other] };
+        r == YES
+    }
+}
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// A dynamic byte buffer in memory.
+    pub NSMutableData : NSData;
+}
+impl NSMutableData {
+    #[inline(always)]
+    fn alloc() -> *mut Object {
+        unsafe { msg_send![class!(NSMutableData), alloc] }
+    }
+
+    /// Creates an empty mutable data object.
+    #[inline(always)]
+    pub fn new() -> Result<CocoaMutableObject<Self>, ()> {
+        unsafe { CocoaMutableObject::from_retained_id(msg_send![Self::alloc(), init]).ok_or(()) }
+    }
+
+    /// Creates an empty mutable data object with enough allocated memory to initially hold a given number of bytes.
+    #[inline(always)]
+    pub fn with_capacity(cap: NSUInteger) -> Result<CocoaMutableObject<Self>, ()> {
+        unsafe { CocoaMutableObject::from_retained_id(msg_send![Self::alloc(), initWithCapacity: cap]).ok_or(()) }
+    }
+
+    /// Creates a mutable data object by copying the bytes.
+    #[inline(always)]
+    pub fn from_slice(bytes: &[u8]) -> Result<CocoaMutableObject<Self>, ()> {
+        let (p, len) = (bytes.as_ptr() as *const c_void, bytes.len() as NSUInteger);
+        unsafe {
+            CocoaMutableObject::from_retained_id(msg_send![Self::alloc(), initWithBytes: p length: len]).ok_or(())
+        }
+    }
+
+    /// Returns the bytes of the data object for modification.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        let len = self.len();
+        let p: *mut u8 = unsafe { msg_send![self, mutableBytes] };
+        if p.is_null() || len == 0 {
+            return &mut [];
+        }
+
+        unsafe { core::slice::from_raw_parts_mut(p, len as _) }
+    }
+
+    /// Appends the bytes to the end of the data object.
+    #[inline(always)]
+    pub fn append(&mut self, bytes: &[u8]) {
+        let (p, len) = (bytes.as_ptr() as *const c_void, bytes.len() as NSUInteger);
+        let _: () = unsafe { msg_send![self, appendBytes: p length: len] };
+    }
+
+    /// Appends the contents of another data object.
+    #[inline(always)]
+    pub fn append_data(&mut self, data: &NSData) {
+        let _: () = unsafe { msg_send![self, appendData: data] };
+    }
+
+    /// Replaces the bytes in the range with other bytes, resizing the data object if the lengths differ.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    #[inline]
+    pub fn replace(&mut self, range: Range<NSUInteger>, bytes: &[u8]) {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range out of bounds"
+        );
+
+        let r = NSRange::from(range);
+        let (p, len) = (bytes.as_ptr() as *const c_void, bytes.len() as NSUInteger);
+        let _: () = unsafe { msg_send![self, replaceBytesInRange: r withBytes: p length: len] };
+    }
+
+    /// Resizes the data object. Extended bytes are filled with zero.
+    #[inline(always)]
+    pub fn set_len(&mut self, len: NSUInteger) {
+        let _: () = unsafe { msg_send![self, setLength: len] };
+    }
+
+    /// Removes all bytes from the data object.
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.set_len(0);
+    }
+}
+impl AsMut<[u8]> for NSMutableData {
+    #[inline(always)]
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.as_bytes_mut()
+    }
+}
+impl Extend<u8> for NSMutableData {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        let bytes = iter.into_iter().collect::<Vec<_>>();
+        self.append(&bytes);
+    }
+}
+impl<'a> Extend<&'a u8> for NSMutableData {
+    fn extend<I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// An object that represents the location of a resource, such as an item on a remote server or the path to a local
+    /// file.
+    pub NSURL : NSObject;
+}
+unsafe impl NSCopying for NSURL {}
+impl NSURL {
+    /// Creates a URL by parsing a string, or returns `Err` if the string is not a valid URL.
+    #[inline(always)]
+    pub fn new(url: &str) -> Result<CocoaObject<Self>, ()> {
+        let u = CFURL::new(url).ok_or(())?;
+
+        Ok(CocoaObject::retain(AsRef::<NSURL>::as_ref(&*u)))
+    }
+
+    /// Creates a file URL for a path. Relative paths are resolved against the current directory.
+    ///
+    /// The path bytes are used as-is, so non-UTF-8 paths are kept intact.
+    #[inline(always)]
+    pub fn from_file_path(path: &std::path::Path, is_directory: bool) -> Result<CocoaObject<Self>, ()> {
+        let u = CFURL::from_file_path(path, is_directory).ok_or(())?;
+
+        Ok(CocoaObject::retain(AsRef::<NSURL>::as_ref(&*u)))
+    }
+
+    /// Returns the path of a file URL in the file system representation, or `None` if the URL is not a file URL.
+    #[inline(always)]
+    pub fn to_file_path(&self) -> Option<std::path::PathBuf> {
+        AsRef::<CFURL>::as_ref(self).to_file_path()
+    }
+
+    /// Returns true if the URL uses the `file` scheme.
+    #[inline(always)]
+    pub fn is_file_url(&self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isFileURL] };
+        r == YES
+    }
+
+    /// The URL string for the receiver as an absolute URL.
+    #[inline(always)]
+    pub fn absolute_string(&self) -> Option<CocoaObject<NSString>> {
+        let p: *mut NSString = unsafe { msg_send![self, absoluteString] };
+        // the string is autoreleased
+        unsafe { p.as_ref() }.map(CocoaObject::retain)
+    }
+
+    /// The scheme of the URL (e.g. `https`, `file`).
+    #[inline(always)]
+    pub fn scheme(&self) -> Option<CocoaObject<NSString>> {
+        let p: *mut NSString = unsafe { msg_send![self, scheme] };
+        unsafe { p.as_ref() }.map(CocoaObject::retain)
+    }
+
+    /// The host of the URL.
+    #[inline(always)]
+    pub fn host(&self) -> Option<CocoaObject<NSString>> {
+        let p: *mut NSString = unsafe { msg_send![self, host] };
+        unsafe { p.as_ref() }.map(CocoaObject::retain)
+    }
+
+    /// The path of the URL, with percent escapes decoded.
+    #[inline(always)]
+    pub fn path(&self) -> Option<CocoaObject<NSString>> {
+        let p: *mut NSString = unsafe { msg_send![self, path] };
+        unsafe { p.as_ref() }.map(CocoaObject::retain)
+    }
+
+    /// Makes the resource pointed by a security-scoped URL available to the app.
+    ///
+    /// Returns false if the URL is not security-scoped or the access is denied. Every successful call must be
+    /// balanced by [`NSURL::stop_accessing_security_scoped_resource`].
+    #[inline(always)]
+    pub fn start_accessing_security_scoped_resource(&self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, startAccessingSecurityScopedResource] };
+        r == YES
+    }
+
+    /// Revokes the access granted by [`NSURL::start_accessing_security_scoped_resource`].
+    #[inline(always)]
+    pub fn stop_accessing_security_scoped_resource(&self) {
+        let _: () = unsafe { msg_send![self, stopAccessingSecurityScopedResource] };
+    }
+}
+impl PartialEq for NSURL {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isEqual: other] };
+        r == YES
+    }
+}
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// A representation of the code and resources stored in a bundle directory on disk.
+    pub NSBundle : NSObject;
+}
+impl NSBundle {
+    /// Returns the bundle object that contains the current executable.
+    #[inline(always)]
+    pub fn main() -> Result<&'static Self, ()> {
         let p: *mut Object = unsafe { msg_send![class!(NSBundle), mainBundle] };
         unsafe { (p as *const NSBundle).as_ref().ok_or(()) }
     }
 
+    /// Returns the bundle object with the specified identifier, if the bundle is already loaded.
+    #[inline(always)]
+    pub fn with_identifier(identifier: &NSString) -> Option<&'static Self> {
+        let p: *mut Object = unsafe { msg_send![class!(NSBundle), bundleWithIdentifier: identifier] };
+        unsafe { (p as *const NSBundle).as_ref() }
+    }
+
+    /// Opens the bundle directory at the path. The executable code of the bundle is not loaded yet.
+    #[inline]
+    pub fn with_path(path: &std::path::Path) -> Result<CocoaObject<Self>, ()> {
+        let url = NSURL::from_file_path(path, true)?;
+        unsafe {
+            let p: *mut Object = msg_send![class!(NSBundle), alloc];
+            CocoaObject::from_retained_id(msg_send![p, initWithURL: &*url]).ok_or(())
+        }
+    }
+
+    /// Dynamically loads the executable code of the bundle into the running program. Returns false on failure.
+    #[inline(always)]
+    pub fn load(&self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, load] };
+        r == YES
+    }
+
+    /// Returns true if the executable code of the bundle is loaded.
+    #[inline(always)]
+    pub fn is_loaded(&self) -> bool {
+        let r: BOOL = unsafe { msg_send![self, isLoaded] };
+        r == YES
+    }
+
+    /// The identifier of the bundle (`CFBundleIdentifier`).
+    #[inline(always)]
+    pub fn bundle_identifier(&self) -> Option<&NSString> {
+        let p: *mut Object = unsafe { msg_send![self, bundleIdentifier] };
+        unsafe { (p as *const NSString).as_ref() }
+    }
+
+    /// The full path of the bundle directory.
+    #[inline]
+    pub fn bundle_path(&self) -> Option<std::path::PathBuf> {
+        let p: *mut Object = unsafe { msg_send![self, bundleURL] };
+        unsafe { (p as *const NSURL).as_ref() }?.to_file_path()
+    }
+
+    /// The full path of the subdirectory containing the resources of the bundle.
+    #[inline]
+    pub fn resource_path(&self) -> Option<std::path::PathBuf> {
+        let p: *mut Object = unsafe { msg_send![self, resourceURL] };
+        unsafe { (p as *const NSURL).as_ref() }?.to_file_path()
+    }
+
+    /// Returns the file URL of the resource identified by a name and a file extension.
+    #[inline]
+    pub fn url_for_resource(&self, name: &NSString, ext: Option<&NSString>) -> Option<CocoaObject<NSURL>> {
+        let ext = ext.map_or(core::ptr::null(), |e| e as *const NSString);
+        let p: *mut Object = unsafe { msg_send![self, URLForResource: name withExtension: ext] };
+
+        // autoreleased
+        unsafe { (p as *const NSURL).as_ref() }.map(CocoaObject::retain)
+    }
+
+    /// Returns the full path of the resource identified by a name and a file extension.
+    #[inline(always)]
+    pub fn path_for_resource(&self, name: &NSString, ext: Option<&NSString>) -> Option<std::path::PathBuf> {
+        self.url_for_resource(name, ext)?.to_file_path()
+    }
+
+    /// Returns the localized version of the string for the key, from the table (`Localizable` if `None`).
+    ///
+    /// The key itself is returned if the string is not localized.
+    #[inline]
+    pub fn localized_string(&self, key: &NSString, table: Option<&NSString>) -> Result<CocoaObject<NSString>, ()> {
+        let table = table.map_or(core::ptr::null(), |t| t as *const NSString);
+        let value = core::ptr::null::<NSString>();
+        let p: *mut Object = unsafe { msg_send![self, localizedStringForKey: key value: value table: table] };
+
+        // autoreleased
+        unsafe { (p as *const NSString).as_ref() }
+            .map(CocoaObject::retain)
+            .ok_or(())
+    }
+
     /// Returns the value associated with the specified key in the receiver's information property list.
     #[inline(always)]
     pub unsafe fn object_for_info_dictionary_key<V>(&self, key: &NSString) -> Option<&V> {
         let p: *mut Object = msg_send![self, objectForInfoDictionaryKey: key];
         (p as *const V).as_ref()
     }
+
+    /// Returns the string value for the key in the information property list, or `None` if the key is missing or
+    /// its value is not a string.
+    pub fn info_string(&self, key: &NSString) -> Option<String> {
+        let v = unsafe { self.object_for_info_dictionary_key::<CFType>(key)? };
+
+        Some(v.downcast::<CFString>()?.to_string())
+    }
+
+    /// The release version number of the bundle (`CFBundleShortVersionString`).
+    #[inline(always)]
+    pub fn short_version(&self) -> Option<String> {
+        self.info_string(ns_string!("CFBundleShortVersionString"))
+    }
+
+    /// The build version number of the bundle (`CFBundleVersion`).
+    #[inline(always)]
+    pub fn version(&self) -> Option<String> {
+        self.info_string(ns_string!("CFBundleVersion"))
+    }
+
+    /// The user-visible name of the bundle (`CFBundleDisplayName`, or `CFBundleName` if not specified).
+    #[inline]
+    pub fn display_name(&self) -> Option<String> {
+        self.info_string(ns_string!("CFBundleDisplayName"))
+            .or_else(|| self.info_string(ns_string!("CFBundleName")))
+    }
 }
 
 objc_ext::DefineObjcObjectWrapper! {
@@ -393,14 +2019,221 @@ impl NSProcessInfo {
 
     /// The name of the process.
     #[inline(always)]
-    pub fn name(&self) -> &NSString {
-        unsafe {
-            let p: *mut Object = msg_send![self, processName];
-            &*(p as *const NSString)
+    pub fn name(&self) -> CocoaObject<NSString> {
+        let p: *mut NSString = unsafe { msg_send![self, processName] };
+        // the name is autoreleased, and replaced by `setProcessName:`
+        CocoaObject::retain(unsafe { &*p })
+    }
+
+    /// The command-line arguments of the process, including the executable path.
+    #[inline(always)]
+    pub fn arguments(&self) -> CocoaObject<NSArray<NSString>> {
+        let p: *mut NSArray<NSString> = unsafe { msg_send![self, arguments] };
+        // the array is autoreleased
+        CocoaObject::retain(unsafe { &*p })
+    }
+
+    /// The environment variables of the process.
+    #[inline(always)]
+    pub fn environment(&self) -> CocoaObject<NSDictionary<NSString, NSString>> {
+        let p: *mut NSDictionary<NSString, NSString> = unsafe { msg_send![self, environment] };
+        // the dictionary is autoreleased
+        CocoaObject::retain(unsafe { &*p })
+    }
+
+    /// The version of the operating system on which the process is executing.
+    #[inline(always)]
+    pub fn operating_system_version(&self) -> NSOperatingSystemVersion {
+        unsafe { msg_send![self, operatingSystemVersion] }
+    }
+
+    /// Returns true if the version of the operating system is the same or later than the given version.
+    #[inline(always)]
+    pub fn is_operating_system_at_least(&self, version: NSOperatingSystemVersion) -> bool {
+        self.operating_system_version() >= version
+    }
+
+    /// The amount of physical memory on the computer in bytes.
+    #[inline(always)]
+    pub fn physical_memory(&self) -> u64 {
+        unsafe { msg_send![self, physicalMemory] }
+    }
+
+    /// The number of processing cores available on the computer.
+    #[inline(always)]
+    pub fn processor_count(&self) -> NSUInteger {
+        unsafe { msg_send![self, processorCount] }
+    }
+
+    /// The number of active processing cores available on the computer.
+    #[inline(always)]
+    pub fn active_processor_count(&self) -> NSUInteger {
+        unsafe { msg_send![self, activeProcessorCount] }
+    }
+
+    /// The current thermal state of the system.
+    #[inline]
+    pub fn thermal_state(&self) -> NSProcessInfoThermalState {
+        let s: NSInteger = unsafe { msg_send![self, thermalState] };
+        match s {
+            0 => NSProcessInfoThermalState::Nominal,
+            1 => NSProcessInfoThermalState::Fair,
+            2 => NSProcessInfoThermalState::Serious,
+            _ => NSProcessInfoThermalState::Critical,
+        }
+    }
+
+    /// Returns true if the user enabled Low Power Mode. Always false before macOS 12.0.
+    #[inline]
+    pub fn is_low_power_mode_enabled(&self) -> bool {
+        let available: BOOL = unsafe { msg_send![self, respondsToSelector: sel!(isLowPowerModeEnabled)] };
+        if available != YES {
+            return false;
+        }
+
+        let r: BOOL = unsafe { msg_send![self, isLowPowerModeEnabled] };
+        r == YES
+    }
+
+    /// Calls the callback with the new thermal state whenever it changes, until the returned observer is dropped.
+    ///
+    /// The callback may run on any thread.
+    pub fn on_thermal_state_change<F: FnMut(NSProcessInfoThermalState) + Send + 'static>(
+        mut callback: F,
+    ) -> CFNotificationObserver {
+        let name = unsafe { &*(NSProcessInfoThermalStateDidChangeNotification as *const NSString) };
+        CFNotificationCenter::local().add_observer(name.as_ref(), move |_| {
+            if let Ok(p) = NSProcessInfo::current() {
+                callback(p.thermal_state());
+            }
+        })
+    }
+
+    /// Calls the callback with the Low Power Mode state whenever it changes, until the returned observer is dropped.
+    ///
+    /// The callback may run on any thread. Fails before macOS 12.0, where the notification does not exist.
+    pub fn on_power_state_change<F: FnMut(bool) + Send + 'static>(
+        mut callback: F,
+    ) -> Result<CFNotificationObserver, ()> {
+        let name = power_state_did_change_notification().ok_or(())?;
+        Ok(CFNotificationCenter::local().add_observer(name.as_ref(), move |_| {
+            if let Ok(p) = NSProcessInfo::current() {
+                callback(p.is_low_power_mode_enabled());
+            }
+        }))
+    }
+
+    /// Begins an activity, which stays in effect until the returned assertion is dropped.
+    ///
+    /// Use [`NSActivityOptions::USER_INITIATED`] and [`NSActivityOptions::IDLE_DISPLAY_SLEEP_DISABLED`] to keep the
+    /// app out of App Nap and the display awake, e.g. while a game is played.
+    #[inline]
+    pub fn begin_activity(&self, options: NSActivityOptions, reason: &NSString) -> Result<NSActivity, ()> {
+        let options = options.bits();
+        let p: *mut Object = unsafe { msg_send![self, beginActivityWithOptions: options reason: reason] };
+
+        // autoreleased
+        let token = unsafe { (p as *const NSObject).as_ref() }.ok_or(())?;
+        Ok(NSActivity(CocoaObject::retain(token)))
+    }
+}
+
+/// The version of an operating system, ordered by its components.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NSOperatingSystemVersion {
+    pub major_version: NSInteger,
+    pub minor_version: NSInteger,
+    pub patch_version: NSInteger,
+}
+impl NSOperatingSystemVersion {
+    #[inline(always)]
+    pub const fn new(major_version: NSInteger, minor_version: NSInteger, patch_version: NSInteger) -> Self {
+        Self {
+            major_version,
+            minor_version,
+            patch_version,
+        }
+    }
+}
+impl core::fmt::Display for NSOperatingSystemVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.major_version, self.minor_version, self.patch_version
+        )
+    }
+}
+
+/// Values used to indicate the system's thermal state, in increasing order of severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NSProcessInfoThermalState {
+    /// The thermal state is within normal limits.
+    Nominal,
+    /// The thermal state is slightly elevated.
+    Fair,
+    /// The thermal state is high. Fans are at maximum speed and the performance may be reduced.
+    Serious,
+    /// The thermal state is significantly impacting the performance of the system.
+    Critical,
+}
+
+bitflags! {
+    /// Options for the activities started with [`NSProcessInfo::begin_activity`].
+    pub struct NSActivityOptions: u64 {
+        /// Requires the screen to stay powered on.
+        const IDLE_DISPLAY_SLEEP_DISABLED = 1 << 40;
+        /// Prevents idle sleep.
+        const IDLE_SYSTEM_SLEEP_DISABLED = 1 << 20;
+        /// Prevents sudden termination.
+        const SUDDEN_TERMINATION_DISABLED = 1 << 14;
+        /// Prevents automatic termination.
+        const AUTOMATIC_TERMINATION_DISABLED = 1 << 15;
+        /// Flags for user-requested work, including [`NSActivityOptions::IDLE_SYSTEM_SLEEP_DISABLED`].
+        const USER_INITIATED = 0x00FF_FFFF | Self::IDLE_SYSTEM_SLEEP_DISABLED.bits;
+        /// Flags for user-requested work that allows idle system sleep.
+        const USER_INITIATED_ALLOWING_IDLE_SYSTEM_SLEEP = Self::USER_INITIATED.bits & !Self::IDLE_SYSTEM_SLEEP_DISABLED.bits;
+        /// Flags for work that the app performed in the background.
+        const BACKGROUND = 0x0000_00FF;
+        /// Flags for work that needs the highest timer accuracy.
+        const LATENCY_CRITICAL = 0xFF_0000_0000;
+    }
+}
+
+/// An activity started with [`NSProcessInfo::begin_activity`], which ends on drop.
+pub struct NSActivity(CocoaObject<NSObject>);
+impl Drop for NSActivity {
+    fn drop(&mut self) {
+        if let Ok(p) = NSProcessInfo::current() {
+            let _: () = unsafe { msg_send![p, endActivity: &*self.0] };
         }
     }
 }
 
+/// The name of a notification (an `NSString`).
+pub type NSNotificationName = *const Object;
+
+#[cfg_attr(target_vendor = "apple", link(name = "Foundation", kind = "framework"))]
+unsafe extern "system" {
+    /// Posted when the thermal state of the system changes.
+    pub unsafe static NSProcessInfoThermalStateDidChangeNotification: NSNotificationName;
+}
+
+/// Returns `NSProcessInfoPowerStateDidChangeNotification`, which is posted when the power state (Low Power Mode) of
+/// the system changes.
+///
+/// The symbol is looked up at runtime, as it only exists since macOS 12.0.
+pub fn power_state_did_change_notification() -> Option<&'static NSString> {
+    let p = unsafe {
+        libc::dlsym(
+            libc::RTLD_DEFAULT,
+            c"NSProcessInfoPowerStateDidChangeNotification".as_ptr(),
+        )
+    };
+    unsafe { (p as *const NSNotificationName).as_ref()?.cast::<NSString>().as_ref() }
+}
+
 pub type NSAttributedStringKey = NSString;
 objc_ext::DefineObjcObjectWrapper! {
     /// A string that has associated attributes for portions of its text.
@@ -430,6 +2263,71 @@ impl NSAttributedString {
     }
 }
 
+/// A number of seconds.
+pub type NSTimeInterval = core::ffi::c_double;
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// A representation of a specific point in time, independent of any calendar or time zone.
+    pub NSDate : NSObject;
+}
+unsafe impl NSCopying for NSDate {}
+impl NSDate {
+    #[inline(always)]
+    fn alloc() -> *mut Object {
+        unsafe { msg_send![class!(NSDate), alloc] }
+    }
+
+    /// Creates a date object set to the current date and time.
+    #[inline(always)]
+    pub fn now() -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), init]).ok_or(()) }
+    }
+
+    /// Creates a date object set to a number of seconds from 00:00:00 UTC on 1 January 2001.
+    #[inline(always)]
+    pub fn with_time_interval_since_reference_date(t: NSTimeInterval) -> Result<CocoaObject<Self>, ()> {
+        unsafe {
+            CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithTimeIntervalSinceReferenceDate: t]).ok_or(())
+        }
+    }
+
+    /// Creates a date object set to a number of seconds from 00:00:00 UTC on 1 January 1970.
+    #[inline(always)]
+    pub fn with_time_interval_since_1970(t: NSTimeInterval) -> Result<CocoaObject<Self>, ()> {
+        unsafe { CocoaObject::from_retained_id(msg_send![Self::alloc(), initWithTimeIntervalSince1970: t]).ok_or(()) }
+    }
+
+    /// Creates a date object for the system time.
+    #[inline(always)]
+    pub fn from_system_time(t: std::time::SystemTime) -> Result<CocoaObject<Self>, ()> {
+        Self::with_time_interval_since_reference_date(crate::absolute_time_from_system_time(t))
+    }
+
+    /// The interval between the date object and 00:00:00 UTC on 1 January 2001.
+    #[inline(always)]
+    pub fn time_interval_since_reference_date(&self) -> NSTimeInterval {
+        unsafe { msg_send![self, timeIntervalSinceReferenceDate] }
+    }
+
+    /// The interval between the date object and 00:00:00 UTC on 1 January 1970.
+    #[inline(always)]
+    pub fn time_interval_since_1970(&self) -> NSTimeInterval {
+        unsafe { msg_send![self, timeIntervalSince1970] }
+    }
+
+    /// Returns the interval between the date object and another date (positive if the date is later).
+    #[inline(always)]
+    pub fn time_interval_since(&self, other: &NSDate) -> NSTimeInterval {
+        unsafe { msg_send![self, timeIntervalSinceDate: other] }
+    }
+
+    /// Returns the date as a system time, or `None` if it is out of the range of `SystemTime`.
+    #[inline(always)]
+    pub fn to_system_time(&self) -> Option<std::time::SystemTime> {
+        crate::system_time_from_absolute_time(self.time_interval_since_reference_date())
+    }
+}
+
 pub type NSErrorDomain = NSString;
 pub type NSErrorUserInfoKey = NSString;
 
@@ -1,9 +1,11 @@
 //! CoreFoundation/Cocoa Framework
+//!
+//! Without the `std` feature, the crate is `no_std` (with `alloc`) and only provides the CoreFoundation-style
+//! bindings: geometry, range and audio format types and the CoreFoundation smart pointers.
 
-use objc::{msg_send, runtime::Object, sel, sel_impl};
-use objc_ext::ObjcObject;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use std::borrow::ToOwned;
+extern crate alloc;
 
 // strictly defined ffi object: https://doc.rust-lang.org/nomicon/ffi.html#representing-opaque-structs
 macro_rules! DefineOpaqueFFIObject {
@@ -30,18 +32,16 @@ macro_rules! DefineCoreObject {
     };
 }
 
-objc_ext::DefineObjcObjectWrapper!(pub NSObject);
-impl NSObject {
-    #[inline(always)]
-    pub fn retain(&self) -> *mut Self {
-        let p: *mut Object = unsafe { msg_send![self, retain] };
-        p as *mut Self
-    }
-
-    #[inline(always)]
-    pub fn release(&self) {
-        let _: () = unsafe { msg_send![self, release] };
-    }
+/// Declares the runtime type identifier of a CoreFoundation object type
+macro_rules! DeclareCFTypeID {
+    ($name: ty = $f: ident) => {
+        impl $crate::corefoundation::CFTypeInfo for $name {
+            #[inline(always)]
+            fn type_id() -> $crate::corefoundation::CFTypeID {
+                unsafe { $f() }
+            }
+        }
+    };
 }
 
 #[cfg(target_pointer_width = "64")]
@@ -54,6 +54,7 @@ pub type NSInteger = i32;
 pub type NSUInteger = u32;
 
 /// Declares toll-free bridge
+#[cfg_attr(not(feature = "std"), allow(unused_macros))]
 macro_rules! TollfreeBridge {
     (mut $a: ty = $b: ty) => {
         impl AsRef<$a> for $b {
@@ -100,213 +101,143 @@ macro_rules! TollfreeBridge {
 pub type OSType = u32;
 pub type OSStatus = i32;
 
+#[cfg(feature = "std")]
+mod cocoa;
+#[cfg(feature = "std")]
+pub use cocoa::*;
 mod corefoundation;
 pub use corefoundation::*;
+#[cfg(feature = "std")]
 mod foundation;
+#[cfg(feature = "std")]
 pub use foundation::*;
+#[cfg(feature = "std")]
 mod appkit;
+#[cfg(feature = "std")]
 pub use appkit::*;
 mod coregraphics;
 pub use coregraphics::*;
 mod corevideo;
 pub use corevideo::*;
+#[cfg(feature = "std")]
 mod coreanimation;
+#[cfg(feature = "std")]
 pub use coreanimation::*;
 mod coretext;
 pub use coretext::*;
 mod audiotoolbox;
 pub use audiotoolbox::*;
+mod utf16;
+pub use utf16::*;
+mod local_executor;
+pub use local_executor::*;
+#[cfg(feature = "std")]
+mod executor;
+pub mod plist;
+#[cfg(feature = "std")]
+pub use executor::*;
 
-/// A smart pointer for NSObject children
-#[repr(transparent)]
-pub struct CocoaObject<T: ObjcObject>(*const T);
-unsafe impl<T: ObjcObject + Sync> Sync for CocoaObject<T> {}
-unsafe impl<T: ObjcObject + Send> Send for CocoaObject<T> {}
-impl<T: ObjcObject> Clone for CocoaObject<T> {
-    #[inline(always)]
-    fn clone(&self) -> Self {
-        let _: *mut Object = unsafe { msg_send![self.id(), retain] };
-        Self(self.0)
-    }
-}
-impl<T: ObjcObject> Drop for CocoaObject<T> {
-    #[inline(always)]
-    fn drop(&mut self) {
-        let _: () = unsafe { msg_send![self.id(), release] };
-    }
-}
-impl<T: ObjcObject> CocoaObject<T> {
-    pub const fn id(&self) -> *const Object {
-        self.0 as _
-    }
-
-    pub const fn into_id(self) -> *const Object {
-        let id = self.id();
-        // no drop executes
-        core::mem::forget(self);
-
-        id
-    }
-
-    pub const unsafe fn from_retained_ptr_unchecked(ptr: *const T) -> Self {
-        Self(ptr)
-    }
-
-    pub const unsafe fn from_retained_id_unchecked(id: *const Object) -> Self {
-        Self(id as _)
-    }
-
-    #[inline(always)]
-    pub unsafe fn from_retained_ptr(ptr: *const T) -> Option<Self> {
-        if ptr.is_null() {
-            None
-        } else {
-            Some(Self::from_retained_ptr_unchecked(ptr))
-        }
-    }
-
-    #[inline(always)]
-    pub unsafe fn from_retained_id(id: *const Object) -> Option<Self> {
-        if id.is_null() {
-            None
-        } else {
-            Some(Self::from_retained_id_unchecked(id))
-        }
-    }
+pub type UniChar = u16;
 
-    pub fn retain(obj: &T) -> Self
-    where
-        T: objc::Message,
-    {
-        let _: *mut Object = unsafe { msg_send![obj, retain] };
-        Self(obj)
-    }
-}
-impl<T: ObjcObject> core::ops::Deref for CocoaObject<T> {
-    type Target = T;
+/// A value indicating that a requested item couldn't be found or doesn't exist.
+#[allow(non_upper_case_globals)]
+pub const NSNotFound: NSInteger = NSInteger::MAX;
 
-    #[inline(always)]
-    fn deref(&self) -> &T {
-        unsafe { &*self.0 }
-    }
+/// A structure used to describe a portion of a series, such as characters in a string or objects in an array.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NSRange {
+    pub location: NSUInteger,
+    pub length: NSUInteger,
 }
-impl<T: ObjcObject> core::borrow::Borrow<T> for CocoaObject<T> {
+impl NSRange {
     #[inline(always)]
-    fn borrow(&self) -> &T {
-        unsafe { &*self.0 }
+    pub const fn new(location: NSUInteger, length: NSUInteger) -> Self {
+        Self { location, length }
     }
-}
 
-/// A smart pointer for NSObject children
-#[repr(transparent)]
-pub struct CocoaMutableObject<T: ObjcObject>(core::ptr::NonNull<T>);
-unsafe impl<T: ObjcObject + Sync> Sync for CocoaMutableObject<T> {}
-unsafe impl<T: ObjcObject + Send> Send for CocoaMutableObject<T> {}
-impl<T: ObjcObject> Clone for CocoaMutableObject<T> {
-    #[inline(always)]
-    fn clone(&self) -> Self {
-        let _: *mut Object = unsafe { msg_send![self.id(), retain] };
-        Self(self.0)
-    }
-}
-impl<T: ObjcObject> Drop for CocoaMutableObject<T> {
+    /// The range returned by searching methods when nothing was found.
     #[inline(always)]
-    fn drop(&mut self) {
-        let _: () = unsafe { msg_send![self.id(), release] };
-    }
-}
-impl<T: ObjcObject> CocoaMutableObject<T> {
-    pub const fn id(&self) -> *mut Object {
-        self.0.as_ptr() as _
-    }
-
-    pub const fn into_id(self) -> *mut Object {
-        let id = self.id();
-        // no drop executed(moveout a pointer with its ownership)
-        core::mem::forget(self);
-
-        id
-    }
-
-    pub const fn from_retained_ptr_unchecked(ptr: core::ptr::NonNull<T>) -> Self {
-        Self(ptr)
-    }
-
-    pub const unsafe fn from_retained_id_unchecked(id: core::ptr::NonNull<Object>) -> Self {
-        Self(core::ptr::NonNull::new_unchecked(id.as_ptr() as _))
+    pub const fn not_found() -> Self {
+        Self {
+            location: NSNotFound as _,
+            length: 0,
+        }
     }
 
+    /// Returns true if the location is `NSNotFound`.
     #[inline(always)]
-    pub fn from_retained_ptr(id: *mut T) -> Option<Self> {
-        Some(Self(core::ptr::NonNull::new(id)?))
+    pub const fn is_not_found(&self) -> bool {
+        self.location == NSNotFound as NSUInteger
     }
 
+    /// Returns the index past the last item of the range, saturating at `NSUInteger::MAX`.
     #[inline(always)]
-    pub fn from_retained_id(id: *mut Object) -> Option<Self> {
-        Self::from_retained_ptr(id as _)
+    pub const fn end(&self) -> NSUInteger {
+        self.location.saturating_add(self.length)
     }
 
-    #[inline(always)]
-    pub fn retain(ptr: &mut T) -> Self
-    where
-        T: objc::Message,
-    {
-        let _: *mut Object = unsafe { msg_send![ptr, retain] };
-        Self(core::ptr::NonNull::from(ptr))
-    }
-}
-impl<T: ObjcObject> core::ops::Deref for CocoaMutableObject<T> {
-    type Target = T;
+    /// Converts into a range of indices, or `None` if the location is `NSNotFound` or the range overflows.
+    #[inline]
+    pub fn to_range(&self) -> Option<core::ops::Range<usize>> {
+        if self.is_not_found() {
+            return None;
+        }
+        let start = usize::try_from(self.location).ok()?;
+        let end = start.checked_add(usize::try_from(self.length).ok()?)?;
 
-    #[inline(always)]
-    fn deref(&self) -> &Self::Target {
-        unsafe { self.0.as_ref() }
-    }
-}
-impl<T: ObjcObject> core::ops::DerefMut for CocoaMutableObject<T> {
-    #[inline(always)]
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { self.0.as_mut() }
+        Some(start..end)
     }
 }
-impl<T: ObjcObject> core::borrow::Borrow<T> for CocoaMutableObject<T> {
-    #[inline(always)]
-    fn borrow(&self) -> &T {
-        unsafe { self.0.as_ref() }
-    }
-}
-impl<T: ObjcObject> core::borrow::BorrowMut<T> for CocoaMutableObject<T> {
-    #[inline(always)]
-    fn borrow_mut(&mut self) -> &mut T {
-        unsafe { self.0.as_mut() }
+#[cfg(feature = "std")]
+unsafe impl objc::Encode for NSRange {
+    fn encode() -> objc::Encoding {
+        unsafe {
+            objc::Encoding::from_str(&format!(
+                "{{_NSRange={}{}}}",
+                NSUInteger::encode().as_str(),
+                NSUInteger::encode().as_str()
+            ))
+        }
     }
 }
-
-impl ToOwned for NSMenuItem {
-    type Owned = CocoaObject<Self>;
-
-    #[inline(always)]
-    fn to_owned(&self) -> Self::Owned {
-        CocoaObject::retain(self)
+impl From<core::ops::Range<NSUInteger>> for NSRange {
+    /// Reversed ranges are converted to empty ranges.
+    fn from(r: core::ops::Range<NSUInteger>) -> Self {
+        NSRange {
+            location: r.start,
+            length: r.end.saturating_sub(r.start),
+        }
     }
 }
+impl From<core::ops::RangeInclusive<NSUInteger>> for NSRange {
+    /// Empty ranges (e.g. `5..=3`) are converted to empty ranges, and the length saturates at `NSUInteger::MAX`.
+    fn from(r: core::ops::RangeInclusive<NSUInteger>) -> Self {
+        if r.is_empty() {
+            return NSRange {
+                location: *r.start(),
+                length: 0,
+            };
+        }
 
-pub type UniChar = u16;
-
-#[repr(C)]
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct NSRange {
-    pub location: NSUInteger,
-    pub length: NSUInteger,
-}
-impl From<std::ops::Range<NSUInteger>> for NSRange {
-    fn from(r: std::ops::Range<NSUInteger>) -> Self {
         NSRange {
-            location: r.start,
-            length: r.end - r.start,
+            location: *r.start(),
+            length: (r.end() - r.start()).saturating_add(1),
         }
     }
 }
+impl TryFrom<CFRange> for NSRange {
+    type Error = ();
+
+    /// Fails if the location or length is negative.
+    #[inline]
+    fn try_from(r: CFRange) -> Result<Self, ()> {
+        Ok(NSRange {
+            location: NSUInteger::try_from(r.location).map_err(drop)?,
+            length: NSUInteger::try_from(r.length).map_err(drop)?,
+        })
+    }
+}
 
 const fn opt_pointer<T>(opt: Option<&T>) -> *const T {
     match opt {
@@ -321,3 +252,57 @@ const fn opt_pointer_mut<T>(opt: Option<&mut T>) -> *mut T {
         None => core::ptr::null_mut(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn nsrange_from_ranges() {
+        assert_eq!(NSRange::from(2..5), NSRange::new(2, 3));
+        assert_eq!(NSRange::from(5..3), NSRange::new(5, 0));
+        assert_eq!(NSRange::from(2..=4), NSRange::new(2, 3));
+        assert_eq!(NSRange::from(4..=4), NSRange::new(4, 1));
+        assert_eq!(NSRange::from(5..=3), NSRange::new(5, 0));
+        assert_eq!(NSRange::from(0..=NSUInteger::MAX), NSRange::new(0, NSUInteger::MAX));
+        assert_eq!(NSRange::from(1..=NSUInteger::MAX), NSRange::new(1, NSUInteger::MAX));
+
+        let mut exhausted = 0..=0;
+        exhausted.next();
+        assert_eq!(NSRange::from(exhausted).length, 0);
+    }
+
+    #[test]
+    fn nsrange_to_range() {
+        for start in 0..8 {
+            for end in start..8 {
+                let r = NSRange::from(start..end);
+                assert_eq!(r.end(), end);
+                assert_eq!(r.to_range(), Some(start as usize..end as usize));
+                if end > start {
+                    assert_eq!(NSRange::from(start..=end - 1), r);
+                }
+            }
+        }
+
+        assert_eq!(NSRange::not_found().to_range(), None);
+        assert!(NSRange::not_found().is_not_found());
+        assert_eq!(NSRange::new(NSUInteger::MAX, 1).to_range(), None);
+        assert_eq!(NSRange::new(NSUInteger::MAX, 1).end(), NSUInteger::MAX);
+    }
+
+    #[test]
+    fn nsrange_from_cfrange() {
+        for location in 0..8 {
+            for length in 0..8 {
+                let r = NSRange::try_from(CFRange::new(location, length)).unwrap();
+                assert_eq!(r, NSRange::new(location as _, length as _));
+                assert_eq!(CFRange::try_from(r), Ok(CFRange::new(location, length)));
+            }
+        }
+
+        assert_eq!(NSRange::try_from(CFRange::new(-1, 2)), Err(()));
+        assert_eq!(NSRange::try_from(CFRange::new(1, -2)), Err(()));
+    }
+}
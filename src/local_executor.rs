@@ -0,0 +1,512 @@
+//! Single-threaded async executor, independent of the event loop which drives it
+//!
+//! Tasks are polled on the thread that owns the executor. Wakers may be used from any thread: waking a task queues
+//! it and asks the event loop (through [`Wakeup`]) to call [`LocalExecutor::run_ready`] soon.
+
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::task::Wake;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::{RefCell, UnsafeCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+
+/// Requests the event loop to run the ready tasks of an executor.
+pub trait Wakeup: Send + Sync + 'static {
+    /// Called when a task becomes ready. The event loop should call [`LocalExecutor::run_ready`] soon.
+    fn wake(&self);
+}
+
+/// The ids of the tasks waiting to be polled, behind a spin lock: the critical sections are a single push or swap.
+struct ReadyQueue {
+    locked: AtomicBool,
+    ids: UnsafeCell<VecDeque<usize>>,
+}
+unsafe impl Sync for ReadyQueue {}
+impl ReadyQueue {
+    const fn new() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            ids: UnsafeCell::new(VecDeque::new()),
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut VecDeque<usize>) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let r = f(unsafe { &mut *self.ids.get() });
+        self.locked.store(false, Ordering::Release);
+
+        r
+    }
+}
+
+struct Shared<W> {
+    ready: ReadyQueue,
+    wakeup: W,
+}
+
+struct TaskWaker<W> {
+    id: usize,
+    queued: AtomicBool,
+    shared: Arc<Shared<W>>,
+}
+impl<W: Wakeup> Wake for TaskWaker<W> {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if self.queued.swap(true, Ordering::AcqRel) {
+            // already waiting to be polled
+            return;
+        }
+
+        self.shared.ready.with(|r| r.push_back(self.id));
+        self.shared.wakeup.wake();
+    }
+}
+
+struct Task<W> {
+    future: Option<Pin<Box<dyn Future<Output = ()>>>>,
+    waker: Arc<TaskWaker<W>>,
+    /// Set when a nested [`LocalExecutor::run_ready`] finds the task queued while it is being polled.
+    woken_while_running: bool,
+}
+
+/// A single-threaded executor, independent of the event loop which drives it.
+pub struct LocalExecutor<W: Wakeup> {
+    shared: Arc<Shared<W>>,
+    tasks: RefCell<Vec<Option<Task<W>>>>,
+    free: RefCell<Vec<usize>>,
+}
+impl<W: Wakeup> LocalExecutor<W> {
+    /// Creates an executor which notifies `wakeup` when tasks become ready.
+    pub fn new(wakeup: W) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                ready: ReadyQueue::new(),
+                wakeup,
+            }),
+            tasks: RefCell::new(Vec::new()),
+            free: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Spawns a future onto the executor. The future is first polled on the next [`LocalExecutor::run_ready`].
+    pub fn spawn<F: Future + 'static>(&self, future: F) -> JoinHandle<F::Output> {
+        let state = Rc::new(RefCell::new(JoinState {
+            result: None,
+            waker: None,
+        }));
+        let s = state.clone();
+        let future = Box::pin(async move {
+            let r = future.await;
+            let mut s = s.borrow_mut();
+            s.result = Some(r);
+            if let Some(w) = s.waker.take() {
+                w.wake();
+            }
+        });
+
+        let mut tasks = self.tasks.borrow_mut();
+        let id = self.free.borrow_mut().pop().unwrap_or(tasks.len());
+        let waker = Arc::new(TaskWaker {
+            id,
+            queued: AtomicBool::new(false),
+            shared: self.shared.clone(),
+        });
+        let task = Task {
+            future: Some(future),
+            waker: waker.clone(),
+            woken_while_running: false,
+        };
+        if id == tasks.len() {
+            tasks.push(Some(task));
+        } else {
+            tasks[id] = Some(task);
+        }
+        drop(tasks);
+        waker.wake_by_ref();
+
+        JoinHandle { state }
+    }
+
+    /// Polls the tasks that were ready when called, and returns the number of tasks polled.
+    ///
+    /// Tasks woken while running are polled on the next call, so a task which always wakes itself cannot starve
+    /// the event loop.
+    pub fn run_ready(&self) -> usize {
+        let ready = self.shared.ready.with(core::mem::take);
+
+        let mut polled = 0;
+        for id in ready {
+            // the slot is released while polling, so that the task can spawn other tasks
+            let (mut future, waker) = {
+                let mut tasks = self.tasks.borrow_mut();
+                let Some(task) = tasks.get_mut(id).and_then(Option::as_mut) else {
+                    continue;
+                };
+                let Some(future) = task.future.take() else {
+                    // the task is being polled by an outer call, which queues it again once the poll returns
+                    task.woken_while_running = true;
+                    continue;
+                };
+                task.waker.queued.store(false, Ordering::Release);
+
+                (future, task.waker.clone())
+            };
+
+            polled += 1;
+            let w = Waker::from(waker);
+            let mut cx = Context::from_waker(&w);
+            match future.as_mut().poll(&mut cx) {
+                Poll::Pending => {
+                    let requeue = match self.tasks.borrow_mut()[id].as_mut() {
+                        Some(task) => {
+                            task.future = Some(future);
+                            core::mem::take(&mut task.woken_while_running)
+                        }
+                        None => false,
+                    };
+                    if requeue {
+                        self.shared.ready.with(|r| r.push_back(id));
+                        self.shared.wakeup.wake();
+                    }
+                }
+                Poll::Ready(()) => {
+                    self.tasks.borrow_mut()[id] = None;
+                    self.free.borrow_mut().push(id);
+                }
+            }
+        }
+
+        polled
+    }
+
+    /// Returns the number of tasks which are not completed yet.
+    pub fn len(&self) -> usize {
+        self.tasks.borrow().iter().filter(|t| t.is_some()).count()
+    }
+
+    /// Returns true if every spawned task is completed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if some tasks are waiting to be polled.
+    pub fn has_ready(&self) -> bool {
+        !self.shared.ready.with(|r| r.is_empty())
+    }
+}
+
+struct JoinState<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// A future that resolves to the output of a spawned task.
+///
+/// Dropping the handle does not cancel the task.
+pub struct JoinHandle<T> {
+    state: Rc<RefCell<JoinState<T>>>,
+}
+impl<T> JoinHandle<T> {
+    /// Returns true if the task is completed.
+    pub fn is_finished(&self) -> bool {
+        self.state.borrow().result.is_some()
+    }
+
+    /// Takes the output if the task is completed.
+    pub fn take_output(&self) -> Option<T> {
+        self.state.borrow_mut().result.take()
+    }
+}
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+        let mut s = self.state.borrow_mut();
+        match s.result.take() {
+            Some(r) => Poll::Ready(r),
+            None => {
+                s.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+struct Channel<T> {
+    queue: VecDeque<T>,
+    senders: usize,
+    waker: Option<Waker>,
+}
+
+/// Creates a channel which turns callbacks (e.g. delegate methods or menu actions) into a stream of values.
+///
+/// Both ends live on the same thread.
+pub fn callback_channel<T>() -> (CallbackSender<T>, CallbackReceiver<T>) {
+    let ch = Rc::new(RefCell::new(Channel {
+        queue: VecDeque::new(),
+        senders: 1,
+        waker: None,
+    }));
+
+    (
+        CallbackSender { ch: ch.clone() },
+        CallbackReceiver { ch, keep_alive: None },
+    )
+}
+
+/// The sending side of [`callback_channel`], to be called from callbacks.
+pub struct CallbackSender<T> {
+    ch: Rc<RefCell<Channel<T>>>,
+}
+impl<T> CallbackSender<T> {
+    /// Sends a value to the receiver.
+    pub fn send(&self, v: T) {
+        let mut ch = self.ch.borrow_mut();
+        ch.queue.push_back(v);
+        if let Some(w) = ch.waker.take() {
+            w.wake();
+        }
+    }
+}
+impl<T> Clone for CallbackSender<T> {
+    fn clone(&self) -> Self {
+        self.ch.borrow_mut().senders += 1;
+
+        Self { ch: self.ch.clone() }
+    }
+}
+impl<T> Drop for CallbackSender<T> {
+    fn drop(&mut self) {
+        let mut ch = self.ch.borrow_mut();
+        ch.senders -= 1;
+        if ch.senders == 0 {
+            if let Some(w) = ch.waker.take() {
+                w.wake();
+            }
+        }
+    }
+}
+
+/// The receiving side of [`callback_channel`].
+pub struct CallbackReceiver<T> {
+    ch: Rc<RefCell<Channel<T>>>,
+    keep_alive: Option<Box<dyn Any>>,
+}
+impl<T> CallbackReceiver<T> {
+    /// Keeps `v` (e.g. the object which calls the sender) alive until the receiver is dropped.
+    pub fn keep_alive(&mut self, v: impl Any) {
+        self.keep_alive = Some(Box::new(v));
+    }
+
+    /// Polls the next value. Resolves to `None` once every sender is dropped and all values are received.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<T>> {
+        let mut ch = self.ch.borrow_mut();
+        if let Some(v) = ch.queue.pop_front() {
+            return Poll::Ready(Some(v));
+        }
+        if ch.senders == 0 {
+            return Poll::Ready(None);
+        }
+
+        ch.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Waits for the next value. Resolves to `None` once every sender is dropped and all values are received.
+    pub fn recv(&mut self) -> Recv<'_, T> {
+        Recv { rx: self }
+    }
+}
+impl<T> Future for CallbackReceiver<T> {
+    type Output = Option<T>;
+
+    /// Waits for a value, for callbacks that fire once (e.g. window close).
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        self.get_mut().poll_next(cx)
+    }
+}
+
+/// A future returned by [`CallbackReceiver::recv`].
+pub struct Recv<'r, T> {
+    rx: &'r mut CallbackReceiver<T>,
+}
+impl<T> Future for Recv<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        self.get_mut().rx.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+    use core::sync::atomic::AtomicUsize;
+    use std::sync::Mutex;
+
+    #[derive(Clone, Default)]
+    struct CountingWakeup(Arc<AtomicUsize>);
+    impl Wakeup for CountingWakeup {
+        fn wake(&self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+    impl CountingWakeup {
+        fn count(&self) -> usize {
+            self.0.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Returns `Pending` once after waking itself.
+    struct YieldNow(bool);
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn spawned_task_runs_on_next_run_ready() {
+        let w = CountingWakeup::default();
+        let e = LocalExecutor::new(w.clone());
+        let h = e.spawn(async { 42 });
+        assert_eq!(w.count(), 1);
+        assert!(e.has_ready());
+        assert!(!h.is_finished());
+
+        assert_eq!(e.run_ready(), 1);
+        assert!(h.is_finished());
+        assert!(e.is_empty());
+        assert_eq!(e.run_ready(), 0);
+    }
+
+    #[test]
+    fn self_waking_task_is_polled_on_next_call() {
+        let w = CountingWakeup::default();
+        let e = LocalExecutor::new(w.clone());
+        let h = e.spawn(YieldNow(false));
+
+        assert_eq!(e.run_ready(), 1);
+        assert!(!h.is_finished());
+        assert_eq!(w.count(), 2);
+        assert_eq!(e.run_ready(), 1);
+        assert!(h.is_finished());
+        assert!(!e.has_ready());
+    }
+
+    #[test]
+    fn wake_from_another_thread() {
+        let w = CountingWakeup::default();
+        let e = LocalExecutor::new(w.clone());
+        let slot = Arc::new(Mutex::new(None::<Waker>));
+        let s = slot.clone();
+        let h = e.spawn(core::future::poll_fn(move |cx| {
+            let mut s = s.lock().unwrap();
+            if s.is_some() {
+                return Poll::Ready(());
+            }
+            *s = Some(cx.waker().clone());
+            Poll::Pending
+        }));
+
+        assert_eq!(e.run_ready(), 1);
+        assert_eq!(e.run_ready(), 0);
+        let waker = slot.lock().unwrap().clone().unwrap();
+        std::thread::spawn(move || {
+            waker.wake_by_ref();
+            // a second wake before polling queues the task only once
+            waker.wake();
+        })
+        .join()
+        .unwrap();
+        assert_eq!(w.count(), 2);
+        assert_eq!(e.run_ready(), 1);
+        assert!(h.is_finished());
+    }
+
+    #[test]
+    fn reentrant_run_ready_keeps_running_task_queued() {
+        let w = CountingWakeup::default();
+        let e = Rc::new(LocalExecutor::new(w.clone()));
+        let inner = Rc::new(Cell::new((usize::MAX, 0)));
+        let (e2, i2, w2) = (e.clone(), inner.clone(), w.clone());
+        let h = e.spawn(async move {
+            YieldNow(false).await;
+            // the task is queued again while being polled, and a nested run loop runs the executor
+            core::future::poll_fn(|cx| {
+                cx.waker().wake_by_ref();
+                Poll::Ready(())
+            })
+            .await;
+            let wakes = w2.count();
+            let polled = e2.run_ready();
+            i2.set((polled, w2.count() - wakes));
+            YieldNow(false).await;
+        });
+
+        assert_eq!(e.run_ready(), 1);
+        let wakes = w.count();
+        assert_eq!(e.run_ready(), 1);
+        // the nested call neither polls nor signals the event loop again; the outer call queues the task once
+        assert_eq!(inner.get(), (0, 0));
+        assert_eq!(w.count(), wakes + 2);
+        assert!(e.has_ready());
+        assert_eq!(e.run_ready(), 1);
+        assert!(h.is_finished());
+    }
+
+    #[test]
+    fn callback_channel_delivers_in_order() {
+        let e = LocalExecutor::new(CountingWakeup::default());
+        let (tx, mut rx) = callback_channel::<u32>();
+        let got = Rc::new(RefCell::new(Vec::new()));
+        let g = got.clone();
+        let h = e.spawn(async move {
+            while let Some(v) = rx.recv().await {
+                g.borrow_mut().push(v);
+            }
+        });
+
+        e.run_ready();
+        tx.send(1);
+        tx.send(2);
+        e.run_ready();
+        assert_eq!(*got.borrow(), [1, 2]);
+        let tx2 = tx.clone();
+        drop(tx);
+        tx2.send(3);
+        e.run_ready();
+        assert!(!h.is_finished());
+        drop(tx2);
+        e.run_ready();
+        assert_eq!(*got.borrow(), [1, 2, 3]);
+        assert!(h.is_finished());
+    }
+}
@@ -0,0 +1,1163 @@
+//! Property lists
+//!
+//! A pure Rust reader/writer of the XML and binary (`bplist00`) property list formats, and conversion between
+//! [`PlistValue`] trees and CoreFoundation property list objects.
+
+use crate::{
+    CFAbsoluteTime, CFArray, CFArrayOf, CFBoolean, CFData, CFDate, CFDictionary, CFDictionaryOf, CFNumber, CFString,
+    CFType, CoreRetainedObject,
+};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::fmt::Write;
+
+/// A value of a property list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlistValue {
+    String(String),
+    /// A signed integer. The readers reject integers above `i64::MAX`.
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    /// Seconds since the reference date (1 Jan 2001 00:00:00 GMT).
+    Date(CFAbsoluteTime),
+    Data(Vec<u8>),
+    Array(Vec<PlistValue>),
+    Dictionary(BTreeMap<String, PlistValue>),
+}
+impl PlistValue {
+    /// Returns the value of a key, if the value is a dictionary.
+    pub fn get(&self, key: &str) -> Option<&PlistValue> {
+        match self {
+            Self::Dictionary(d) => d.get(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if the value is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if the value is an integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Integer(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value as a floating value, if the value is a real or an integer.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Real(v) => Some(v),
+            Self::Integer(v) => Some(v as _),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean, if the value is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Boolean(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements, if the value is an array.
+    pub fn as_array(&self) -> Option<&[PlistValue]> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries, if the value is a dictionary.
+    pub fn as_dictionary(&self) -> Option<&BTreeMap<String, PlistValue>> {
+        match self {
+            Self::Dictionary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Reads a property list in either XML or binary format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PlistError> {
+        if bytes.starts_with(BINARY_MAGIC) {
+            Self::from_binary(bytes)
+        } else {
+            Self::from_xml(core::str::from_utf8(bytes).map_err(|_| PlistError::Syntax {
+                offset: 0,
+                message: "not UTF-8",
+            })?)
+        }
+    }
+
+    /// Reads a property list in XML format.
+    pub fn from_xml(src: &str) -> Result<Self, PlistError> {
+        XmlReader { src, pos: 0, depth: 0 }.document()
+    }
+
+    /// Writes the value as a property list in XML format.
+    pub fn to_xml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+            "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+            "<plist version=\"1.0\">\n"
+        ));
+        write_xml(&mut out, self, 0);
+        out.push_str("</plist>\n");
+
+        out
+    }
+
+    /// Reads a property list in binary format.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, PlistError> {
+        BinaryReader::new(bytes)?.document()
+    }
+
+    /// Writes the value as a property list in binary format.
+    pub fn to_binary(&self) -> Vec<u8> {
+        BinaryWriter::default().document(self)
+    }
+
+    /// Creates a CoreFoundation property list object of the value.
+    pub fn to_cf(&self) -> Option<CoreRetainedObject<CFType>> {
+        Some(match self {
+            Self::String(s) => CFString::new(s)?.into_cftype(),
+            Self::Integer(v) => CFNumber::new(*v)?.into_cftype(),
+            Self::Real(v) => CFNumber::new(*v)?.into_cftype(),
+            Self::Boolean(v) => CoreRetainedObject::retain(CFBoolean::new(*v)).into_cftype(),
+            Self::Date(v) => CFDate::new(*v)?.into_cftype(),
+            Self::Data(v) => CFData::new(v)?.into_cftype(),
+            Self::Array(v) => {
+                let values = v.iter().map(Self::to_cf).collect::<Option<Vec<_>>>()?;
+
+                let array = CFArrayOf::new(&values.iter().map(|v| &**v).collect::<Vec<_>>())?;
+
+                CoreRetainedObject::retain(&**array).into_cftype()
+            }
+            Self::Dictionary(d) => {
+                let keys = d.keys().map(|k| CFString::new(k)).collect::<Option<Vec<_>>>()?;
+                let values = d.values().map(Self::to_cf).collect::<Option<Vec<_>>>()?;
+
+                let dict = CFDictionaryOf::new(
+                    &keys.iter().map(|v| &**v).collect::<Vec<_>>(),
+                    &values.iter().map(|v| &**v).collect::<Vec<_>>(),
+                )?;
+
+                CoreRetainedObject::retain(&**dict).into_cftype()
+            }
+        })
+    }
+
+    /// Converts a CoreFoundation property list object. Returns `None` if the object is not a property list type.
+    pub fn from_cf(v: &CFType) -> Option<Self> {
+        if let Some(s) = v.downcast::<CFString>() {
+            Some(Self::String(s.to_str().into_owned()))
+        } else if let Some(n) = v.downcast::<CFNumber>() {
+            if n.is_float_type() {
+                Some(Self::Real(n.get_lossy()))
+            } else {
+                Some(Self::Integer(n.get_lossy()))
+            }
+        } else if let Some(b) = v.downcast::<CFBoolean>() {
+            Some(Self::Boolean(b.value()))
+        } else if let Some(d) = v.downcast::<CFDate>() {
+            Some(Self::Date(d.absolute_time()))
+        } else if let Some(d) = v.downcast::<CFData>() {
+            Some(Self::Data(d.bytes().to_vec()))
+        } else if let Some(a) = v.downcast::<CFArray>() {
+            unsafe { a.as_typed_unchecked::<CFType>() }
+                .iter()
+                .map(Self::from_cf)
+                .collect::<Option<_>>()
+                .map(Self::Array)
+        } else if let Some(d) = v.downcast::<CFDictionary>() {
+            unsafe { d.as_typed_unchecked::<CFType, CFType>() }
+                .iter()
+                .map(|(k, v)| Some((k.downcast::<CFString>()?.to_str().into_owned(), Self::from_cf(v)?)))
+                .collect::<Option<_>>()
+                .map(Self::Dictionary)
+        } else {
+            None
+        }
+    }
+}
+
+/// An error while reading a property list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlistError {
+    /// The XML document is malformed, or is not a property list.
+    Syntax { offset: usize, message: &'static str },
+    /// The binary property list is malformed.
+    Binary(&'static str),
+}
+impl core::fmt::Display for PlistError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Syntax { offset, message } => write!(f, "invalid XML property list at byte {offset}: {message}"),
+            Self::Binary(message) => write!(f, "invalid binary property list: {message}"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for PlistError {}
+
+// dates //
+
+const SECONDS_PER_DAY: i64 = 86400;
+/// Days from 1970-01-01 to 2001-01-01.
+const REFERENCE_DATE_DAYS: i64 = 11323;
+
+/// Days since 1970-01-01 of a civil date.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+/// Civil date of days since 1970-01-01.
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+
+    (if m <= 2 { yoe + era * 400 + 1 } else { yoe + era * 400 }, m, d)
+}
+
+/// Formats a date as `YYYY-MM-DDTHH:MM:SSZ`. Fractional seconds are truncated toward the past.
+///
+/// Dates outside of the years 0000 to 9999, which the format cannot represent, are clamped to that range.
+fn format_date(t: CFAbsoluteTime) -> String {
+    let min = (days_from_civil(0, 1, 1) - REFERENCE_DATE_DAYS) * SECONDS_PER_DAY;
+    let max = (days_from_civil(10000, 1, 1) - REFERENCE_DATE_DAYS) * SECONDS_PER_DAY - 1;
+    let t = t.clamp(min as f64, max as f64);
+    let mut secs = t as i64;
+    if (secs as f64) > t {
+        secs -= 1;
+    }
+    let days = secs.div_euclid(SECONDS_PER_DAY);
+    let rem = secs.rem_euclid(SECONDS_PER_DAY);
+    let (y, m, d) = civil_from_days(days + REFERENCE_DATE_DAYS);
+
+    alloc::format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// Parses a date in `YYYY-MM-DDTHH:MM:SSZ` format.
+fn parse_date(s: &str) -> Option<CFAbsoluteTime> {
+    let b = s.as_bytes();
+    if b.len() != 20 || b[4] != b'-' || b[7] != b'-' || b[10] != b'T' || b[13] != b':' || b[16] != b':' || b[19] != b'Z'
+    {
+        return None;
+    }
+    let n = |r: core::ops::Range<usize>| -> Option<i64> {
+        let s = &s[r];
+        if s.bytes().all(|c| c.is_ascii_digit()) {
+            s.parse().ok()
+        } else {
+            None
+        }
+    };
+    let (y, mo, d, h, mi, se) = (n(0..4)?, n(5..7)?, n(8..10)?, n(11..13)?, n(14..16)?, n(17..19)?);
+    if !(1..=12).contains(&mo) || !(1..=31).contains(&d) || h > 23 || mi > 59 || se > 60 {
+        return None;
+    }
+    let days = days_from_civil(y, mo, d) - REFERENCE_DATE_DAYS;
+
+    Some((days * SECONDS_PER_DAY + h * 3600 + mi * 60 + se) as _)
+}
+
+// base64 //
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for c in bytes.chunks(3) {
+        let n = (c[0] as u32) << 16 | (*c.get(1).unwrap_or(&0) as u32) << 8 | *c.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= c.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in s.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+// XML //
+
+struct XmlReader<'s> {
+    src: &'s str,
+    pos: usize,
+    /// The number of arrays and dictionaries containing the current position.
+    depth: usize,
+}
+/// A start tag, an end tag or an empty element tag.
+enum Tag<'s> {
+    Start(&'s str),
+    End(&'s str),
+    Empty(&'s str),
+}
+impl<'s> XmlReader<'s> {
+    fn error<T>(&self, message: &'static str) -> Result<T, PlistError> {
+        Err(PlistError::Syntax {
+            offset: self.pos,
+            message,
+        })
+    }
+
+    fn rest(&self) -> &'s str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_until(&mut self, pat: &str) -> Result<(), PlistError> {
+        match self.rest().find(pat) {
+            Some(n) => {
+                self.pos += n + pat.len();
+                Ok(())
+            }
+            None => self.error("unterminated markup"),
+        }
+    }
+
+    /// Skips whitespace, comments, processing instructions and the document type declaration.
+    fn skip_misc(&mut self) -> Result<(), PlistError> {
+        loop {
+            self.pos += self.rest().len() - self.rest().trim_start().len();
+            if self.rest().starts_with("<?") {
+                self.skip_until("?>")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if self.rest().starts_with("<!DOCTYPE") {
+                self.skip_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn tag(&mut self) -> Result<Tag<'s>, PlistError> {
+        self.skip_misc()?;
+        if !self.rest().starts_with('<') {
+            return self.error("expected a tag");
+        }
+        let Some(end) = self.rest().find('>') else {
+            return self.error("unterminated tag");
+        };
+        let inner = &self.rest()[1..end];
+        self.pos += end + 1;
+
+        if let Some(name) = inner.strip_prefix('/') {
+            Ok(Tag::End(name.trim()))
+        } else if let Some(inner) = inner.strip_suffix('/') {
+            Ok(Tag::Empty(inner.split_ascii_whitespace().next().unwrap_or("")))
+        } else {
+            Ok(Tag::Start(inner.split_ascii_whitespace().next().unwrap_or("")))
+        }
+    }
+
+    fn end_tag(&mut self, name: &str) -> Result<(), PlistError> {
+        match self.tag()? {
+            Tag::End(n) if n == name => Ok(()),
+            _ => self.error("mismatched end tag"),
+        }
+    }
+
+    /// Reads character data up to the end tag, resolving entity and character references.
+    fn text(&mut self, name: &str) -> Result<String, PlistError> {
+        let mut out = String::new();
+        loop {
+            let rest = self.rest();
+            if let Some(r) = rest.strip_prefix("<![CDATA[") {
+                let Some(n) = r.find("]]>") else {
+                    return self.error("unterminated CDATA section");
+                };
+                out.push_str(&r[..n]);
+                self.pos += 9 + n + 3;
+            } else if rest.starts_with("<!--") {
+                self.skip_until("-->")?;
+            } else if rest.starts_with('<') {
+                self.end_tag(name)?;
+                return Ok(out);
+            } else if let Some(r) = rest.strip_prefix('&') {
+                let Some(n) = r.find(';') else {
+                    return self.error("unterminated reference");
+                };
+                let c = match &r[..n] {
+                    "lt" => '<',
+                    "gt" => '>',
+                    "amp" => '&',
+                    "quot" => '"',
+                    "apos" => '\'',
+                    e => {
+                        let code = if let Some(h) = e.strip_prefix("#x") {
+                            u32::from_str_radix(h, 16).ok()
+                        } else if let Some(d) = e.strip_prefix('#') {
+                            d.parse().ok()
+                        } else {
+                            None
+                        };
+                        match code.and_then(char::from_u32) {
+                            Some(c) => c,
+                            None => return self.error("invalid reference"),
+                        }
+                    }
+                };
+                out.push(c);
+                self.pos += 1 + n + 1;
+            } else {
+                let n = rest.find(['<', '&']).unwrap_or(rest.len());
+                if n == rest.len() {
+                    return self.error("unterminated element");
+                }
+                out.push_str(&rest[..n]);
+                self.pos += n;
+            }
+        }
+    }
+
+    fn document(mut self) -> Result<PlistValue, PlistError> {
+        let v = match self.tag()? {
+            Tag::Start("plist") => {
+                let v = self.value()?;
+                self.end_tag("plist")?;
+                v
+            }
+            // a bare value without the root element
+            t => self.value_of(t)?,
+        };
+        self.skip_misc()?;
+        if !self.rest().is_empty() {
+            return self.error("trailing content");
+        }
+
+        Ok(v)
+    }
+
+    fn value(&mut self) -> Result<PlistValue, PlistError> {
+        let t = self.tag()?;
+        self.value_of(t)
+    }
+
+    fn value_of(&mut self, t: Tag<'s>) -> Result<PlistValue, PlistError> {
+        let start = self.pos;
+        let (name, empty) = match t {
+            Tag::Start(n) => (n, false),
+            Tag::Empty(n) => (n, true),
+            Tag::End(_) => return self.error("unexpected end tag"),
+        };
+        let text = |r: &mut Self| if empty { Ok(String::new()) } else { r.text(name) };
+        let invalid = |r: &mut Self| {
+            r.pos = start;
+            r.error("invalid value")
+        };
+
+        if matches!(name, "array" | "dict") && !empty {
+            if self.depth >= MAX_DEPTH {
+                return self.error("too deeply nested");
+            }
+            self.depth += 1;
+        }
+
+        match name {
+            "true" | "false" => {
+                if !empty {
+                    self.end_tag(name)?;
+                }
+                Ok(PlistValue::Boolean(name == "true"))
+            }
+            "string" => Ok(PlistValue::String(text(self)?)),
+            "integer" => {
+                let s = text(self)?;
+                let s = s.trim();
+                let v = if let Some(h) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                    i64::from_str_radix(h, 16).ok()
+                } else {
+                    // values above `i64::MAX` are rejected, as they cannot be represented
+                    s.parse().ok()
+                };
+                v.map(PlistValue::Integer).map_or_else(|| invalid(self), Ok)
+            }
+            "real" => {
+                let s = text(self)?;
+                let v = match s.trim() {
+                    "nan" => Some(f64::NAN),
+                    "inf" | "+inf" | "infinity" | "+infinity" => Some(f64::INFINITY),
+                    "-inf" | "-infinity" => Some(f64::NEG_INFINITY),
+                    s => s.parse().ok(),
+                };
+                v.map(PlistValue::Real).map_or_else(|| invalid(self), Ok)
+            }
+            "date" => {
+                let s = text(self)?;
+                parse_date(s.trim())
+                    .map(PlistValue::Date)
+                    .map_or_else(|| invalid(self), Ok)
+            }
+            "data" => {
+                let s = text(self)?;
+                base64_decode(&s)
+                    .map(PlistValue::Data)
+                    .map_or_else(|| invalid(self), Ok)
+            }
+            "array" => {
+                let mut v = Vec::new();
+                if !empty {
+                    loop {
+                        match self.tag()? {
+                            Tag::End("array") => break,
+                            t => v.push(self.value_of(t)?),
+                        }
+                    }
+                    self.depth -= 1;
+                }
+                Ok(PlistValue::Array(v))
+            }
+            "dict" => {
+                let mut d = BTreeMap::new();
+                if !empty {
+                    loop {
+                        let key = match self.tag()? {
+                            Tag::End("dict") => break,
+                            Tag::Start("key") => self.text("key")?,
+                            Tag::Empty("key") => String::new(),
+                            _ => return self.error("expected a key"),
+                        };
+                        let v = self.value()?;
+                        d.insert(key, v);
+                    }
+                    self.depth -= 1;
+                }
+                Ok(PlistValue::Dictionary(d))
+            }
+            _ => invalid(self),
+        }
+    }
+}
+
+fn escape_xml(out: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn write_xml(out: &mut String, v: &PlistValue, depth: usize) {
+    let indent = |out: &mut String, depth| out.extend(core::iter::repeat_n('\t', depth));
+    indent(out, depth);
+    match v {
+        PlistValue::String(s) => {
+            out.push_str("<string>");
+            escape_xml(out, s);
+            out.push_str("</string>\n");
+        }
+        PlistValue::Integer(v) => writeln!(out, "<integer>{v}</integer>").unwrap(),
+        PlistValue::Real(v) if v.is_nan() => out.push_str("<real>nan</real>\n"),
+        PlistValue::Real(v) if v.is_infinite() => {
+            writeln!(out, "<real>{}infinity</real>", if *v > 0.0 { "+" } else { "-" }).unwrap()
+        }
+        PlistValue::Real(v) => writeln!(out, "<real>{v}</real>").unwrap(),
+        PlistValue::Boolean(true) => out.push_str("<true/>\n"),
+        PlistValue::Boolean(false) => out.push_str("<false/>\n"),
+        PlistValue::Date(v) => writeln!(out, "<date>{}</date>", format_date(*v)).unwrap(),
+        PlistValue::Data(v) => {
+            out.push_str("<data>\n");
+            for line in base64_encode(v).as_bytes().chunks(68) {
+                indent(out, depth);
+                out.push_str(core::str::from_utf8(line).unwrap());
+                out.push('\n');
+            }
+            indent(out, depth);
+            out.push_str("</data>\n");
+        }
+        PlistValue::Array(a) if a.is_empty() => out.push_str("<array/>\n"),
+        PlistValue::Array(a) => {
+            out.push_str("<array>\n");
+            for v in a {
+                write_xml(out, v, depth + 1);
+            }
+            indent(out, depth);
+            out.push_str("</array>\n");
+        }
+        PlistValue::Dictionary(d) if d.is_empty() => out.push_str("<dict/>\n"),
+        PlistValue::Dictionary(d) => {
+            out.push_str("<dict>\n");
+            for (k, v) in d {
+                indent(out, depth + 1);
+                out.push_str("<key>");
+                escape_xml(out, k);
+                out.push_str("</key>\n");
+                write_xml(out, v, depth + 1);
+            }
+            indent(out, depth);
+            out.push_str("</dict>\n");
+        }
+    }
+}
+
+// binary //
+
+const BINARY_MAGIC: &[u8] = b"bplist00";
+const TRAILER_SIZE: usize = 32;
+/// Nesting limit of both formats, which also rejects reference cycles of binary property lists. The readers are
+/// recursive, so the limit also bounds their stack usage.
+const MAX_DEPTH: usize = 128;
+/// The minimum decoding budget of binary property lists; see [`BinaryReader::spend`].
+const MIN_BUDGET: usize = 1 << 20;
+
+struct BinaryReader<'b> {
+    bytes: &'b [u8],
+    offsets: Vec<usize>,
+    ref_size: usize,
+    top: usize,
+    /// The remaining decoding budget.
+    budget: Cell<usize>,
+}
+impl<'b> BinaryReader<'b> {
+    fn new(bytes: &'b [u8]) -> Result<Self, PlistError> {
+        if !bytes.starts_with(BINARY_MAGIC) || bytes.len() < BINARY_MAGIC.len() + TRAILER_SIZE {
+            return Err(PlistError::Binary("missing header or trailer"));
+        }
+        let trailer = &bytes[bytes.len() - TRAILER_SIZE..];
+        let offset_size = trailer[6] as usize;
+        let ref_size = trailer[7] as usize;
+        let count = be_uint(&trailer[8..16]) as usize;
+        let top = be_uint(&trailer[16..24]) as usize;
+        let table = be_uint(&trailer[24..32]) as usize;
+        if !(1..=8).contains(&offset_size) || !(1..=8).contains(&ref_size) || top >= count {
+            return Err(PlistError::Binary("invalid trailer"));
+        }
+        let table_end = count
+            .checked_mul(offset_size)
+            .and_then(|n| n.checked_add(table))
+            .filter(|&e| e <= bytes.len() - TRAILER_SIZE)
+            .ok_or(PlistError::Binary("offset table out of range"))?;
+        let offsets = bytes[table..table_end]
+            .chunks(offset_size)
+            .map(|c| be_uint(c) as usize)
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            bytes,
+            offsets,
+            ref_size,
+            top,
+            budget: Cell::new(bytes.len().saturating_mul(16).max(MIN_BUDGET)),
+        })
+    }
+
+    /// Accounts `cost` units (one per object plus one per byte of strings and data) to the decoding budget.
+    ///
+    /// Objects are shared by references and decoded every time they are referenced, so a small input can expand
+    /// exponentially; the budget limits the size of the decoded tree to a multiple of the input size.
+    fn spend(&self, cost: usize) -> Result<(), PlistError> {
+        match self.budget.get().checked_sub(cost) {
+            Some(b) => {
+                self.budget.set(b);
+                Ok(())
+            }
+            None => Err(PlistError::Binary("too many objects")),
+        }
+    }
+
+    fn document(self) -> Result<PlistValue, PlistError> {
+        self.object(self.top, 0)
+    }
+
+    fn slice(&self, start: usize, len: usize) -> Result<&'b [u8], PlistError> {
+        start
+            .checked_add(len)
+            .filter(|&e| e <= self.bytes.len() - TRAILER_SIZE)
+            .map(|e| &self.bytes[start..e])
+            .ok_or(PlistError::Binary("object out of range"))
+    }
+
+    /// Reads the element count of an object, returning it with the offset of the contents.
+    fn count(&self, info: u8, at: usize) -> Result<(usize, usize), PlistError> {
+        if info != 0x0f {
+            return Ok((info as usize, at));
+        }
+        let marker = self.slice(at, 1)?[0];
+        if marker >> 4 != 0x1 {
+            return Err(PlistError::Binary("invalid count"));
+        }
+        let size = 1 << (marker & 0x0f);
+        let n = be_uint(self.slice(at + 1, size)?) as usize;
+
+        Ok((n, at + 1 + size))
+    }
+
+    fn refs(&self, at: usize, count: usize) -> Result<Vec<usize>, PlistError> {
+        let len = count
+            .checked_mul(self.ref_size)
+            .ok_or(PlistError::Binary("object out of range"))?;
+
+        Ok(self
+            .slice(at, len)?
+            .chunks(self.ref_size)
+            .map(|c| be_uint(c) as usize)
+            .collect())
+    }
+
+    fn object(&self, index: usize, depth: usize) -> Result<PlistValue, PlistError> {
+        if depth > MAX_DEPTH {
+            return Err(PlistError::Binary("too deeply nested"));
+        }
+        self.spend(1)?;
+        let at = *self
+            .offsets
+            .get(index)
+            .ok_or(PlistError::Binary("invalid object reference"))?;
+        let marker = self.slice(at, 1)?[0];
+        let (kind, info) = (marker >> 4, marker & 0x0f);
+
+        match (kind, info) {
+            (0x0, 0x8) => Ok(PlistValue::Boolean(false)),
+            (0x0, 0x9) => Ok(PlistValue::Boolean(true)),
+            (0x1, 0..=3) => {
+                let b = self.slice(at + 1, 1 << info)?;
+                // 8-byte integers are signed; shorter ones are unsigned
+                Ok(PlistValue::Integer(be_uint(b) as i64))
+            }
+            (0x1, 4) => {
+                let b = self.slice(at + 1, 16)?;
+                let hi = be_uint(&b[..8]) as i64;
+                let lo = be_uint(&b[8..]);
+                if (hi == 0 && lo <= i64::MAX as u64) || (hi == -1 && lo > i64::MAX as u64) {
+                    Ok(PlistValue::Integer(lo as i64))
+                } else {
+                    Err(PlistError::Binary("integer out of range"))
+                }
+            }
+            (0x2, 2) => Ok(PlistValue::Real(
+                f32::from_bits(be_uint(self.slice(at + 1, 4)?) as u32) as f64
+            )),
+            (0x2, 3) => Ok(PlistValue::Real(f64::from_bits(be_uint(self.slice(at + 1, 8)?)))),
+            (0x3, 3) => Ok(PlistValue::Date(f64::from_bits(be_uint(self.slice(at + 1, 8)?)))),
+            (0x4, _) => {
+                let (n, at) = self.count(info, at + 1)?;
+                self.spend(n)?;
+                Ok(PlistValue::Data(self.slice(at, n)?.to_vec()))
+            }
+            (0x5, _) => {
+                let (n, at) = self.count(info, at + 1)?;
+                self.spend(n)?;
+                let b = self.slice(at, n)?;
+                if !b.is_ascii() {
+                    return Err(PlistError::Binary("invalid ASCII string"));
+                }
+                Ok(PlistValue::String(String::from_utf8(b.to_vec()).unwrap()))
+            }
+            (0x6, _) => {
+                let (n, at) = self.count(info, at + 1)?;
+                let len = n.checked_mul(2).ok_or(PlistError::Binary("object out of range"))?;
+                self.spend(len)?;
+                let units = self.slice(at, len)?.chunks(2).map(|c| u16::from_be_bytes([c[0], c[1]]));
+                char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .map(PlistValue::String)
+                    .map_err(|_| PlistError::Binary("invalid UTF-16 string"))
+            }
+            (0x7, _) => {
+                let (n, at) = self.count(info, at + 1)?;
+                self.spend(n)?;
+                let b = self.slice(at, n)?;
+                match b.iter().position(|&c| c == 0) {
+                    Some(e) => core::str::from_utf8(&b[..e])
+                        .map(|s| PlistValue::String(s.to_string()))
+                        .map_err(|_| PlistError::Binary("invalid UTF-8 string")),
+                    None => Err(PlistError::Binary("invalid UTF-8 string")),
+                }
+            }
+            (0xa, _) => {
+                let (n, at) = self.count(info, at + 1)?;
+                self.refs(at, n)?
+                    .into_iter()
+                    .map(|r| self.object(r, depth + 1))
+                    .collect::<Result<_, _>>()
+                    .map(PlistValue::Array)
+            }
+            (0xd, _) => {
+                let (n, at) = self.count(info, at + 1)?;
+                let keys = self.refs(at, n)?;
+                let values = self.refs(at + n * self.ref_size, n)?;
+                let mut d = BTreeMap::new();
+                for (k, v) in keys.into_iter().zip(values) {
+                    let PlistValue::String(k) = self.object(k, depth + 1)? else {
+                        return Err(PlistError::Binary("dictionary key is not a string"));
+                    };
+                    d.insert(k, self.object(v, depth + 1)?);
+                }
+                Ok(PlistValue::Dictionary(d))
+            }
+            _ => Err(PlistError::Binary("unsupported object type")),
+        }
+    }
+}
+
+/// Reads a big-endian unsigned integer of up to 8 bytes.
+fn be_uint(b: &[u8]) -> u64 {
+    b.iter().fold(0, |a, &c| a << 8 | c as u64)
+}
+
+#[derive(Default)]
+struct BinaryWriter<'v> {
+    /// Objects in the order of their indices.
+    objects: Vec<BinaryObject<'v>>,
+}
+enum BinaryObject<'v> {
+    Key(&'v str),
+    Scalar(&'v PlistValue),
+    Array(Vec<usize>),
+    Dictionary(Vec<usize>, Vec<usize>),
+}
+impl<'v> BinaryWriter<'v> {
+    /// Assigns the object indices in depth-first order, returning the index of the value.
+    fn flatten(&mut self, v: &'v PlistValue) -> usize {
+        let index = self.objects.len();
+        self.objects.push(BinaryObject::Scalar(v));
+        match v {
+            PlistValue::Array(a) => {
+                let refs = a.iter().map(|v| self.flatten(v)).collect();
+                self.objects[index] = BinaryObject::Array(refs);
+            }
+            PlistValue::Dictionary(d) => {
+                let keys = d
+                    .keys()
+                    .map(|k| {
+                        self.objects.push(BinaryObject::Key(k));
+                        self.objects.len() - 1
+                    })
+                    .collect();
+                let values = d.values().map(|v| self.flatten(v)).collect();
+                self.objects[index] = BinaryObject::Dictionary(keys, values);
+            }
+            _ => (),
+        }
+
+        index
+    }
+
+    fn document(mut self, v: &'v PlistValue) -> Vec<u8> {
+        self.flatten(v);
+        let ref_size = uint_size(self.objects.len() as u64 - 1);
+        let write_ref = |out: &mut Vec<u8>, r: usize| out.extend_from_slice(&(r as u64).to_be_bytes()[8 - ref_size..]);
+
+        let mut out = BINARY_MAGIC.to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for o in &self.objects {
+            offsets.push(out.len() as u64);
+            match o {
+                BinaryObject::Key(k) => write_string(&mut out, k),
+                BinaryObject::Scalar(v) => write_scalar(&mut out, v),
+                BinaryObject::Array(refs) => {
+                    write_marker(&mut out, 0xa, refs.len());
+                    refs.iter().for_each(|&r| write_ref(&mut out, r));
+                }
+                BinaryObject::Dictionary(keys, values) => {
+                    write_marker(&mut out, 0xd, keys.len());
+                    keys.iter().chain(values).for_each(|&r| write_ref(&mut out, r));
+                }
+            }
+        }
+
+        let table = out.len() as u64;
+        let offset_size = uint_size(table);
+        for o in offsets {
+            out.extend_from_slice(&o.to_be_bytes()[8 - offset_size..]);
+        }
+        out.extend_from_slice(&[0; 6]);
+        out.push(offset_size as u8);
+        out.push(ref_size as u8);
+        out.extend_from_slice(&(self.objects.len() as u64).to_be_bytes());
+        out.extend_from_slice(&0u64.to_be_bytes());
+        out.extend_from_slice(&table.to_be_bytes());
+
+        out
+    }
+}
+
+/// Returns the smallest size of 1, 2, 4 or 8 bytes that can store the value.
+fn uint_size(v: u64) -> usize {
+    match v {
+        0..=0xff => 1,
+        0x100..=0xffff => 2,
+        0x1_0000..=0xffff_ffff => 4,
+        _ => 8,
+    }
+}
+
+fn write_marker(out: &mut Vec<u8>, kind: u8, count: usize) {
+    if count < 15 {
+        out.push(kind << 4 | count as u8);
+    } else {
+        out.push(kind << 4 | 0x0f);
+        write_integer(out, count as i64);
+    }
+}
+
+fn write_integer(out: &mut Vec<u8>, v: i64) {
+    let size = if v < 0 { 8 } else { uint_size(v as u64) };
+    out.push(0x10 | size.trailing_zeros() as u8);
+    out.extend_from_slice(&v.to_be_bytes()[8 - size..]);
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    if s.is_ascii() {
+        write_marker(out, 0x5, s.len());
+        out.extend_from_slice(s.as_bytes());
+    } else {
+        let units = s.encode_utf16().collect::<Vec<_>>();
+        write_marker(out, 0x6, units.len());
+        for u in units {
+            out.extend_from_slice(&u.to_be_bytes());
+        }
+    }
+}
+
+fn write_scalar(out: &mut Vec<u8>, v: &PlistValue) {
+    match v {
+        PlistValue::Boolean(false) => out.push(0x08),
+        PlistValue::Boolean(true) => out.push(0x09),
+        PlistValue::Integer(v) => write_integer(out, *v),
+        PlistValue::Real(v) => {
+            out.push(0x23);
+            out.extend_from_slice(&v.to_bits().to_be_bytes());
+        }
+        PlistValue::Date(v) => {
+            out.push(0x33);
+            out.extend_from_slice(&v.to_bits().to_be_bytes());
+        }
+        PlistValue::Data(d) => {
+            write_marker(out, 0x4, d.len());
+            out.extend_from_slice(d);
+        }
+        PlistValue::String(s) => write_string(out, s),
+        PlistValue::Array(_) | PlistValue::Dictionary(_) => unreachable!("containers are flattened"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::vec;
+
+    macro_rules! fixture {
+        ($name: literal) => {
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/", $name))
+        };
+    }
+
+    fn sample() -> PlistValue {
+        let s = |v: &str| PlistValue::String(v.into());
+        let dict =
+            |e: Vec<(&str, PlistValue)>| PlistValue::Dictionary(e.into_iter().map(|(k, v)| (k.into(), v)).collect());
+        let date = |y, mo, d, h: i64, mi: i64, se: i64| {
+            PlistValue::Date(
+                ((days_from_civil(y, mo, d) - REFERENCE_DATE_DAYS) * SECONDS_PER_DAY + h * 3600 + mi * 60 + se) as _,
+            )
+        };
+
+        dict(vec![
+            ("Name", s("Sample & <Test>")),
+            ("Unicode", s("héllo 😀")),
+            ("Count", PlistValue::Integer(300)),
+            ("Negative", PlistValue::Integer(-5)),
+            ("Max", PlistValue::Integer(i64::MAX)),
+            ("Ratio", PlistValue::Real(0.5)),
+            ("Enabled", PlistValue::Boolean(true)),
+            ("Disabled", PlistValue::Boolean(false)),
+            ("Created", date(1990, 5, 17, 12, 30, 45)),
+            ("Modified", date(2024, 2, 29, 23, 59, 59)),
+            ("Blob", PlistValue::Data((0..100).collect())),
+            ("Empty", dict(vec![])),
+            (
+                "Items",
+                PlistValue::Array(vec![
+                    dict(vec![
+                        ("Name", s("first")),
+                        ("Tags", PlistValue::Array(vec![s("a"), s("b"), s("a")])),
+                    ]),
+                    dict(vec![("Name", s("second")), ("Tags", PlistValue::Array(vec![]))]),
+                    PlistValue::Array(vec![
+                        PlistValue::Integer(1),
+                        PlistValue::Integer(2),
+                        PlistValue::Array(vec![
+                            PlistValue::Integer(3),
+                            PlistValue::Array(vec![PlistValue::Integer(4)]),
+                        ]),
+                    ]),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn read_fixtures() {
+        assert_eq!(PlistValue::from_bytes(fixture!("sample.plist")), Ok(sample()));
+        assert_eq!(PlistValue::from_bytes(fixture!("sample.bplist")), Ok(sample()));
+    }
+
+    #[test]
+    fn xml_output_matches_fixture() {
+        assert_eq!(sample().to_xml().as_bytes(), fixture!("sample.plist"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let v = sample();
+        assert_eq!(PlistValue::from_xml(&v.to_xml()), Ok(v.clone()));
+        assert_eq!(PlistValue::from_binary(&v.to_binary()), Ok(v.clone()));
+
+        let reals = PlistValue::Array(vec![
+            PlistValue::Real(f64::INFINITY),
+            PlistValue::Real(f64::NEG_INFINITY),
+            PlistValue::Real(-0.25),
+            PlistValue::Integer(i64::MIN),
+        ]);
+        assert_eq!(PlistValue::from_xml(&reals.to_xml()), Ok(reals.clone()));
+        assert_eq!(PlistValue::from_binary(&reals.to_binary()), Ok(reals));
+    }
+
+    #[test]
+    fn malformed_binary() {
+        for (bytes, message) in [
+            (&fixture!("truncated.bplist")[..], "invalid trailer"),
+            (fixture!("cyclic.bplist"), "too deeply nested"),
+            (fixture!("exponential.bplist"), "too many objects"),
+            (b"bplist00", "missing header or trailer"),
+        ] {
+            assert_eq!(PlistValue::from_bytes(bytes), Err(PlistError::Binary(message)));
+        }
+
+        let mut b = sample().to_binary();
+        let n = b.len();
+        // the offset table starts past the end
+        b[n - 8..].copy_from_slice(&(n as u64).to_be_bytes());
+        assert_eq!(
+            PlistValue::from_binary(&b),
+            Err(PlistError::Binary("offset table out of range"))
+        );
+    }
+
+    #[test]
+    fn malformed_xml() {
+        let message = |src: &str| match PlistValue::from_xml(src) {
+            Err(PlistError::Syntax { message, .. }) => message,
+            r => panic!("{src:?} was read as {r:?}"),
+        };
+
+        assert_eq!(message("<plist><string>a</plist>"), "mismatched end tag");
+        assert_eq!(
+            message("<plist><dict><string>a</string></dict></plist>"),
+            "expected a key"
+        );
+        assert_eq!(
+            message("<plist><integer>9223372036854775808</integer></plist>"),
+            "invalid value"
+        );
+        assert_eq!(message("<plist><integer>0x</integer></plist>"), "invalid value");
+        assert_eq!(message("<plist><date>2001-01-01</date></plist>"), "invalid value");
+        assert_eq!(message("<plist><data>!!</data></plist>"), "invalid value");
+        assert_eq!(message("<plist><string>&bogus;</string></plist>"), "invalid reference");
+        assert_eq!(message("<plist><true/></plist><true/>"), "trailing content");
+        assert_eq!(message("<plist><string>a"), "unterminated element");
+
+        let deep = format!(
+            "{}<true/>{}",
+            "<array>".repeat(MAX_DEPTH + 1),
+            "</array>".repeat(MAX_DEPTH + 1)
+        );
+        assert_eq!(message(&deep), "too deeply nested");
+        let ok = format!("{}<true/>{}", "<array>".repeat(MAX_DEPTH), "</array>".repeat(MAX_DEPTH));
+        assert!(PlistValue::from_xml(&ok).is_ok());
+    }
+
+    #[test]
+    fn integers() {
+        let read = |s: &str| PlistValue::from_xml(&format!("<integer>{s}</integer>")).map(|v| v.as_i64());
+        assert_eq!(read(" 42 "), Ok(Some(42)));
+        assert_eq!(read("-9223372036854775808"), Ok(Some(i64::MIN)));
+        assert_eq!(read("0x7fffffffffffffff"), Ok(Some(i64::MAX)));
+        assert!(read("18446744073709551615").is_err());
+        assert!(read("0xffffffffffffffff").is_err());
+    }
+
+    #[test]
+    fn dates() {
+        for s in [
+            "0000-01-01T00:00:00Z",
+            "1970-01-01T00:00:00Z",
+            "2001-01-01T00:00:00Z",
+            "9999-12-31T23:59:59Z",
+        ] {
+            assert_eq!(format_date(parse_date(s).unwrap()), s);
+        }
+        assert_eq!(parse_date("2001-01-01T00:00:00Z"), Some(0.0));
+        // fractions are truncated toward the past
+        assert_eq!(format_date(-0.5), "2000-12-31T23:59:59Z");
+
+        // out of range years are clamped, and the output can be read again
+        for (t, s) in [
+            (-1.0e12, "0000-01-01T00:00:00Z"),
+            (f64::NEG_INFINITY, "0000-01-01T00:00:00Z"),
+            (1.0e12, "9999-12-31T23:59:59Z"),
+            (f64::INFINITY, "9999-12-31T23:59:59Z"),
+        ] {
+            assert_eq!(format_date(t), s);
+            assert!(parse_date(s).is_some());
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Offset mapping between UTF-8 strings and UTF-16 based text APIs
+
+use crate::{CFIndex, CFRange, NSRange, NSUInteger};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+const BYTE: usize = 0;
+const CHAR: usize = 1;
+const UTF16: usize = 2;
+
+/// Start offsets and lengths of a non-ASCII character, in bytes, chars and UTF-16 code units.
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    start: [usize; 3],
+    len: [usize; 3],
+}
+
+/// Maps offsets in a string between UTF-8 bytes, chars and UTF-16 code units.
+///
+/// CoreText and Foundation address text in UTF-16 code units (`CFRange`, `NSRange`, string indices of `CTRun`),
+/// while Rust strings are addressed in bytes. Only non-ASCII characters are recorded, so indexing an ASCII string
+/// costs no allocation and each conversion is a binary search.
+#[derive(Debug, Clone)]
+pub struct Utf16Index<'s> {
+    text: &'s str,
+    marks: Vec<Mark>,
+    len: [usize; 3],
+}
+impl<'s> Utf16Index<'s> {
+    /// Builds the index for a string.
+    pub fn new(text: &'s str) -> Self {
+        let mut marks = Vec::new();
+        let (mut chars, mut utf16) = (0, 0);
+        for (byte, c) in text.char_indices() {
+            if !c.is_ascii() {
+                marks.push(Mark {
+                    start: [byte, chars, utf16],
+                    len: [c.len_utf8(), 1, c.len_utf16()],
+                });
+            }
+            chars += 1;
+            utf16 += c.len_utf16();
+        }
+
+        Self {
+            text,
+            marks,
+            len: [text.len(), chars, utf16],
+        }
+    }
+
+    /// Returns the indexed string.
+    #[inline(always)]
+    pub const fn text(&self) -> &'s str {
+        self.text
+    }
+
+    /// Returns the number of chars in the string.
+    #[inline(always)]
+    pub const fn len_chars(&self) -> usize {
+        self.len[CHAR]
+    }
+
+    /// Returns the number of UTF-16 code units in the string.
+    #[inline(always)]
+    pub const fn len_utf16(&self) -> usize {
+        self.len[UTF16]
+    }
+
+    /// Converts an offset from one unit to another.
+    ///
+    /// Returns `None` if the offset is past the end, or does not point a character boundary.
+    fn convert(&self, x: usize, from: usize, to: usize) -> Option<usize> {
+        if x > self.len[from] {
+            return None;
+        }
+
+        // offsets advance equally on ASCII characters between marks
+        let n = self.marks.partition_point(|m| m.start[from] <= x);
+        let Some(m) = n.checked_sub(1).map(|n| &self.marks[n]) else {
+            return Some(x);
+        };
+        match x - m.start[from] {
+            0 => Some(m.start[to]),
+            d if d < m.len[from] => None,
+            d => Some(m.start[to] + m.len[to] + (d - m.len[from])),
+        }
+    }
+
+    /// Converts a byte offset to a UTF-16 offset.
+    #[inline(always)]
+    pub fn byte_to_utf16(&self, byte: usize) -> Option<usize> {
+        self.convert(byte, BYTE, UTF16)
+    }
+
+    /// Converts a UTF-16 offset to a byte offset. Offsets in the middle of a surrogate pair have no byte offset.
+    #[inline(always)]
+    pub fn utf16_to_byte(&self, utf16: usize) -> Option<usize> {
+        self.convert(utf16, UTF16, BYTE)
+    }
+
+    /// Converts a byte offset to a char index.
+    #[inline(always)]
+    pub fn byte_to_char(&self, byte: usize) -> Option<usize> {
+        self.convert(byte, BYTE, CHAR)
+    }
+
+    /// Converts a char index to a byte offset.
+    #[inline(always)]
+    pub fn char_to_byte(&self, ch: usize) -> Option<usize> {
+        self.convert(ch, CHAR, BYTE)
+    }
+
+    /// Converts a char index to a UTF-16 offset.
+    #[inline(always)]
+    pub fn char_to_utf16(&self, ch: usize) -> Option<usize> {
+        self.convert(ch, CHAR, UTF16)
+    }
+
+    /// Converts a UTF-16 offset to a char index. Offsets in the middle of a surrogate pair have no char index.
+    #[inline(always)]
+    pub fn utf16_to_char(&self, utf16: usize) -> Option<usize> {
+        self.convert(utf16, UTF16, CHAR)
+    }
+
+    /// Converts a range of UTF-16 offsets to a range of byte offsets.
+    #[inline]
+    pub fn utf16_range_to_bytes(&self, r: Range<usize>) -> Option<Range<usize>> {
+        if r.start > r.end {
+            return None;
+        }
+
+        Some(self.utf16_to_byte(r.start)?..self.utf16_to_byte(r.end)?)
+    }
+
+    /// Converts a range of byte offsets to a range of UTF-16 offsets.
+    #[inline]
+    pub fn byte_range_to_utf16(&self, r: Range<usize>) -> Option<Range<usize>> {
+        if r.start > r.end {
+            return None;
+        }
+
+        Some(self.byte_to_utf16(r.start)?..self.byte_to_utf16(r.end)?)
+    }
+
+    /// Converts a `CFRange` to a range of byte offsets.
+    ///
+    /// The range is taken literally: a length of 0 is an empty range, not "up to the end".
+    #[inline]
+    pub fn cfrange_to_bytes(&self, r: &CFRange) -> Option<Range<usize>> {
+        self.utf16_range_to_bytes(r.to_range()?)
+    }
+
+    /// Converts an `NSRange` to a range of byte offsets. `NSNotFound` ranges have no byte range.
+    #[inline]
+    pub fn nsrange_to_bytes(&self, r: &NSRange) -> Option<Range<usize>> {
+        self.utf16_range_to_bytes(r.to_range()?)
+    }
+
+    /// Converts a range of byte offsets to a `CFRange`.
+    #[inline]
+    pub fn bytes_to_cfrange(&self, r: Range<usize>) -> Option<CFRange> {
+        let r = self.byte_range_to_utf16(r)?;
+
+        Some(CFRange::new(
+            CFIndex::try_from(r.start).ok()?,
+            CFIndex::try_from(r.len()).ok()?,
+        ))
+    }
+
+    /// Converts a range of byte offsets to an `NSRange`.
+    #[inline]
+    pub fn bytes_to_nsrange(&self, r: Range<usize>) -> Option<NSRange> {
+        let r = self.byte_range_to_utf16(r)?;
+
+        Some(NSRange::new(
+            NSUInteger::try_from(r.start).ok()?,
+            NSUInteger::try_from(r.len()).ok()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_offsets_are_identical() {
+        let x = Utf16Index::new("hello");
+        assert_eq!(x.len_chars(), 5);
+        assert_eq!(x.len_utf16(), 5);
+        for i in 0..=5 {
+            assert_eq!(x.byte_to_utf16(i), Some(i));
+            assert_eq!(x.utf16_to_byte(i), Some(i));
+            assert_eq!(x.char_to_byte(i), Some(i));
+        }
+        assert_eq!(x.byte_to_utf16(6), None);
+        assert_eq!(x.utf16_to_byte(6), None);
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        // "a" (1 byte, 1 unit), "é" (2 bytes, 1 unit), "😀" (4 bytes, 2 units), "b"
+        let s = "aé😀b";
+        let x = Utf16Index::new(s);
+        assert_eq!(x.len_chars(), 4);
+        assert_eq!(x.len_utf16(), s.encode_utf16().count());
+
+        let bytes = [0, 1, 3, 7, 8];
+        let utf16 = [0, 1, 2, 4, 5];
+        for ch in 0..=4 {
+            assert_eq!(x.char_to_byte(ch), Some(bytes[ch]));
+            assert_eq!(x.char_to_utf16(ch), Some(utf16[ch]));
+            assert_eq!(x.byte_to_utf16(bytes[ch]), Some(utf16[ch]));
+            assert_eq!(x.utf16_to_byte(utf16[ch]), Some(bytes[ch]));
+            assert_eq!(x.utf16_to_char(utf16[ch]), Some(ch));
+            assert_eq!(x.byte_to_char(bytes[ch]), Some(ch));
+        }
+
+        // the middle of a surrogate pair, and the middle of multi-byte characters
+        assert_eq!(x.utf16_to_byte(3), None);
+        assert_eq!(x.utf16_to_char(3), None);
+        assert_eq!(x.byte_to_utf16(2), None);
+        for b in 4..7 {
+            assert_eq!(x.byte_to_utf16(b), None);
+        }
+    }
+
+    #[test]
+    fn boundary_indices() {
+        let s = "😀";
+        let x = Utf16Index::new(s);
+        assert_eq!(x.byte_to_utf16(0), Some(0));
+        assert_eq!(x.byte_to_utf16(4), Some(2));
+        assert_eq!(x.byte_to_utf16(5), None);
+        assert_eq!(x.utf16_to_byte(2), Some(4));
+        assert_eq!(x.utf16_to_byte(3), None);
+        assert_eq!(x.char_to_utf16(1), Some(2));
+        assert_eq!(x.char_to_utf16(2), None);
+
+        let empty = Utf16Index::new("");
+        assert_eq!(empty.byte_to_utf16(0), Some(0));
+        assert_eq!(empty.utf16_to_byte(1), None);
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn ranges() {
+        let s = "x😀yé";
+        let x = Utf16Index::new(s);
+        assert_eq!(x.utf16_range_to_bytes(1..3), Some(1..5));
+        assert_eq!(x.utf16_range_to_bytes(1..2), None);
+        assert_eq!(x.utf16_range_to_bytes(3..1), None);
+        assert_eq!(x.byte_range_to_utf16(5..s.len()), Some(3..5));
+        assert_eq!(x.cfrange_to_bytes(&CFRange::new(3, 2)), Some(5..8));
+        assert_eq!(x.cfrange_to_bytes(&CFRange::new(-1, 2)), None);
+        assert_eq!(x.nsrange_to_bytes(&NSRange::not_found()), None);
+        assert_eq!(x.nsrange_to_bytes(&NSRange::new(0, 3)), Some(0..5));
+        assert_eq!(x.bytes_to_cfrange(1..5), Some(CFRange::new(1, 2)));
+        assert_eq!(x.bytes_to_nsrange(5..8), Some(NSRange::new(3, 2)));
+        assert_eq!(x.bytes_to_nsrange(2..8), None);
+
+        for (b, _) in s.char_indices() {
+            let u = x.byte_to_utf16(b).unwrap();
+            assert_eq!(x.utf16_to_byte(u), Some(b));
+            assert_eq!(s[..b].encode_utf16().count(), u);
+        }
+    }
+}
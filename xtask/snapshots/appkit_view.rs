@@ -0,0 +1,64 @@
+// @generated by `cargo xtask gen`. Do not edit by hand.
+
+//! AppKit bindings
+
+use crate::{CALayer, CocoaMutableObject, NSRect, NSSize};
+use objc::{class, msg_send, runtime::*, sel, sel_impl};
+
+objc_ext::DefineObjcObjectWrapper!(pub NSResponder : NSObject);
+
+objc_ext::DefineObjcObjectWrapper! {
+    /// The infrastructure for drawing, printing, and handling events in an app.
+    pub NSView : NSResponder;
+}
+impl NSView {
+    #[inline(always)]
+    pub fn new() -> Result<CocoaMutableObject<Self>, ()> {
+        unsafe { CocoaMutableObject::from_retained_id(msg_send![class!(NSView), new]).ok_or(()) }
+    }
+
+    /// The Core Animation layer that the view uses as its backing store.
+    #[inline(always)]
+    pub fn layer(&self) -> Option<&CALayer> {
+        let p: *mut Object = unsafe { msg_send![self, layer] };
+        unsafe { (p as *const CALayer).as_ref() }
+    }
+
+    /// The Core Animation layer that the view uses as its backing store.
+    #[inline(always)]
+    pub fn layer_mut(&mut self) -> Option<&mut CALayer> {
+        let p: *mut Object = unsafe { msg_send![self, layer] };
+        unsafe { (p as *mut CALayer).as_mut() }
+    }
+
+    /// Sets the view's frame rectangle, which defines its position and size in its superview's coordinate system.
+    #[inline(always)]
+    pub fn set_frame(&mut self, f: NSRect) {
+        let _: () = unsafe { msg_send![self, setFrame: f] };
+    }
+
+    /// Gets the view's frame rectangle, which defines its position and size in its superview's coordinate system.
+    #[inline(always)]
+    pub fn frame(&self) -> NSRect {
+        unsafe { msg_send![self, frame] }
+    }
+
+    /// Converts a size from the view's interior coordinate system to its pixel aligned backing store coordinate system.
+    #[inline(always)]
+    pub fn convert_size_to_backing(&self, size: NSSize) -> NSSize {
+        unsafe { msg_send![self, convertSizeToBacking: size] }
+    }
+
+    /// Sets a boolean value indicating whether the view fills its frame rectangle with opaque content.
+    #[inline(always)]
+    pub fn set_opaque(&mut self, c: bool) {
+        let _: () = unsafe { msg_send![self, setOpaque: if c { YES } else { NO }] };
+    }
+
+    /// A boolean value indicating whether the view is being rendered as part of a live resizing operation.
+    #[inline(always)]
+    pub fn in_live_resize(&self) -> bool {
+        let b: BOOL = unsafe { msg_send![self, inLiveResize] };
+        b == YES
+    }
+}
@@ -0,0 +1,87 @@
+// @generated by `cargo xtask gen`. Do not edit by hand.
+
+//! Core Text
+
+use crate::{opt_pointer, CFArray, CFArrayRef, CFDictionary, CFDictionaryRef, CFIndex, CFRange, CFStringRef, CGFloat};
+use crate::{CGPath, CGPathRef, CGPoint, CoreRetainedObject};
+use objc::{Encode, Encoding};
+
+pub const kCTFontOrientationDefault: u32 = 0;
+
+/// A glyph position paired with its advance.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CTGlyphPlacement {
+    pub position: CGPoint,
+    pub advance: CGFloat,
+}
+unsafe impl Encode for CTGlyphPlacement {
+    fn encode() -> Encoding {
+        unsafe {
+            Encoding::from_str(&format!(
+                "{{CTGlyphPlacement={}{}}}",
+                CGPoint::encode().as_str(),
+                CGFloat::encode().as_str()
+            ))
+        }
+    }
+}
+
+DefineCoreObject! {
+    /// An opaque type that is used to generate text frames.
+    pub CTFramesetter;
+}
+/// A reference to a CTFramesetter object.
+pub type CTFramesetterRef = *const CTFramesetter;
+impl CTFramesetter {
+    /// Creates an immutable frame using a framesetter.
+    #[inline(always)]
+    pub fn create_frame(
+        &self,
+        str_range: CFRange,
+        path: &CGPath,
+        attributes: Option<&CFDictionary>,
+    ) -> Result<CoreRetainedObject<CTFrame>, ()> {
+        unsafe {
+            CoreRetainedObject::retained(CTFramesetterCreateFrame(self, str_range, path, opt_pointer(attributes)))
+                .ok_or(())
+        }
+    }
+}
+
+DefineCoreObject! {
+    /// Represents a frame containing multiple lines of text.
+    pub CTFrame;
+}
+/// A reference to a Core Text frame object.
+pub type CTFrameRef = *const CTFrame;
+impl CTFrame {
+    /// Returns an array of lines stored in the frame.
+    #[inline(always)]
+    pub fn lines(&self) -> Result<&CFArray, ()> {
+        unsafe { CTFrameGetLines(self).as_ref().ok_or(()) }
+    }
+
+    /// Returns the range of characters that actually fit in the frame.
+    #[inline(always)]
+    pub fn visible_string_range(&self) -> CFRange {
+        unsafe { CTFrameGetVisibleStringRange(self) }
+    }
+}
+
+#[cfg_attr(target_vendor = "apple", link(name = "CoreText", kind = "framework"))]
+unsafe extern "system" {
+    unsafe fn CTFramesetterCreateFrame(
+        framesetter: CTFramesetterRef,
+        string_range: CFRange,
+        path: CGPathRef,
+        frame_attributes: CFDictionaryRef,
+    ) -> CTFrameRef;
+    unsafe fn CTFrameGetLines(frame: CTFrameRef) -> CFArrayRef;
+    unsafe fn CTFrameGetVisibleStringRange(frame: CTFrameRef) -> CFRange;
+    /// Exposed as-is.
+    pub unsafe fn CTFrameGetLineCount(frame: CTFrameRef) -> CFIndex;
+
+    pub unsafe static kCTFontAttributeName: CFStringRef;
+    pub unsafe static kCTKernAttributeName: CFStringRef;
+}
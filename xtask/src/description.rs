@@ -0,0 +1,186 @@
+//! Checked-in API description format
+
+use serde::Deserialize;
+
+/// A description of one generated module.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Module {
+    /// Module-level documentation (emitted as `//!` comments).
+    pub doc: Option<String>,
+    /// Framework that the extern block links against.
+    pub framework: Option<String>,
+    /// `use` declarations, emitted verbatim.
+    #[serde(default)]
+    pub uses: Vec<String>,
+    #[serde(default, rename = "constant")]
+    pub constants: Vec<Constant>,
+    #[serde(default, rename = "struct")]
+    pub structs: Vec<Struct>,
+    #[serde(default, rename = "core_object")]
+    pub core_objects: Vec<CoreObject>,
+    #[serde(default, rename = "class")]
+    pub classes: Vec<Class>,
+    #[serde(default, rename = "function")]
+    pub functions: Vec<Function>,
+}
+
+/// A constant. Constants without `value` are declared as extern statics.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Constant {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub value: Option<String>,
+    pub doc: Option<String>,
+}
+
+/// A `#[repr(C)]` structure.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Struct {
+    pub name: String,
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub derive: Vec<String>,
+    /// Emits an `objc::Encode` implementation built from the field encodings.
+    #[serde(default)]
+    pub encode: bool,
+    #[serde(default, rename = "field")]
+    pub fields: Vec<Field>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub doc: Option<String>,
+}
+
+/// An Objective-C class wrapper.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Class {
+    pub name: String,
+    #[serde(default = "default_superclass")]
+    pub superclass: String,
+    pub doc: Option<String>,
+    #[serde(default, rename = "method")]
+    pub methods: Vec<Method>,
+}
+fn default_superclass() -> String {
+    String::from("NSObject")
+}
+
+/// A method implemented with `msg_send!`.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Method {
+    pub name: String,
+    pub selector: String,
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub receiver: Receiver,
+    #[serde(default, rename = "arg")]
+    pub args: Vec<Arg>,
+    pub returns: Option<String>,
+    #[serde(default)]
+    pub kind: MessageReturn,
+}
+
+/// A CoreFoundation-style object wrapper.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CoreObject {
+    pub name: String,
+    pub doc: Option<String>,
+    /// Documentation of the `XXXRef` alias.
+    pub ref_doc: Option<String>,
+    /// Declares `XXXRef` as `*mut` instead of `*const`.
+    #[serde(default)]
+    pub mutable: bool,
+    #[serde(default, rename = "method")]
+    pub methods: Vec<CoreMethod>,
+}
+
+/// A method implemented with a call to a C function.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CoreMethod {
+    pub name: String,
+    pub function: String,
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub receiver: Receiver,
+    #[serde(default, rename = "arg")]
+    pub args: Vec<Arg>,
+    pub returns: Option<String>,
+    #[serde(default)]
+    pub kind: CallReturn,
+}
+
+/// A C function declared in the extern block.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Function {
+    pub name: String,
+    pub doc: Option<String>,
+    #[serde(default)]
+    pub public: bool,
+    #[serde(default, rename = "arg")]
+    pub args: Vec<Arg>,
+    pub returns: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Arg {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Receiver {
+    /// `&self`
+    #[default]
+    Ref,
+    /// `&mut self`
+    Mut,
+    /// no receiver (class method / free-standing creation function)
+    Class,
+}
+
+/// How the result of `msg_send!` is surfaced.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageReturn {
+    /// The value is returned as is.
+    #[default]
+    Value,
+    /// `BOOL` converted to `bool`.
+    Bool,
+    /// An unretained object pointer, returned as `Option<&T>`.
+    Ref,
+    /// A retained object, returned as `Result<CocoaObject<T>, ()>`.
+    Retained,
+    /// A retained object, returned as `Result<CocoaMutableObject<T>, ()>`.
+    RetainedMut,
+}
+
+/// How the result of a C function call is surfaced.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CallReturn {
+    /// The value is returned as is.
+    #[default]
+    Value,
+    /// An object following the Get rule, returned as `Result<&T, ()>`.
+    Borrowed,
+    /// An object following the Create rule, returned as `Result<CoreRetainedObject<T>, ()>`.
+    Retained,
+}
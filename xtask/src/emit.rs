@@ -0,0 +1,354 @@
+//! Rust source emitter
+
+use crate::description::*;
+use std::fmt::Write;
+
+/// Generates the source of a module from its description.
+pub fn module(m: &Module) -> Result<String, String> {
+    let mut out = String::from("// @generated by `cargo xtask gen`. Do not edit by hand.\n\n");
+
+    if let Some(d) = &m.doc {
+        for l in d.lines() {
+            writeln!(out, "//! {l}").unwrap();
+        }
+        out.push('\n');
+    }
+    if !m.uses.is_empty() {
+        for u in &m.uses {
+            writeln!(out, "use {u};").unwrap();
+        }
+        out.push('\n');
+    }
+
+    for c in m.constants.iter().filter(|c| c.value.is_some()) {
+        doc(&mut out, "", c.doc.as_deref());
+        writeln!(
+            out,
+            "pub const {}: {} = {};",
+            c.name,
+            c.type_,
+            c.value.as_deref().unwrap()
+        )
+        .unwrap();
+    }
+    if m.constants.iter().any(|c| c.value.is_some()) {
+        out.push('\n');
+    }
+
+    for s in &m.structs {
+        structure(&mut out, s);
+        out.push('\n');
+    }
+    for o in &m.core_objects {
+        core_object(&mut out, o)?;
+        out.push('\n');
+    }
+    for c in &m.classes {
+        class(&mut out, c)?;
+        out.push('\n');
+    }
+
+    let statics = m.constants.iter().filter(|c| c.value.is_none()).collect::<Vec<_>>();
+    if !m.functions.is_empty() || !statics.is_empty() {
+        let framework = m
+            .framework
+            .as_deref()
+            .ok_or("`framework` is required to declare functions or extern constants")?;
+        writeln!(
+            out,
+            "#[cfg_attr(target_vendor = \"apple\", link(name = {framework:?}, kind = \"framework\"))]"
+        )
+        .unwrap();
+        out.push_str("unsafe extern \"system\" {\n");
+        for f in &m.functions {
+            doc(&mut out, "    ", f.doc.as_deref());
+            let vis = if f.public { "pub " } else { "" };
+            let args = f
+                .args
+                .iter()
+                .map(|a| format!("{}: {}", a.name, a.type_))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(out, "    {vis}unsafe fn {}({args})", f.name).unwrap();
+            if let Some(r) = &f.returns {
+                write!(out, " -> {r}").unwrap();
+            }
+            out.push_str(";\n");
+        }
+        if !m.functions.is_empty() && !statics.is_empty() {
+            out.push('\n');
+        }
+        for c in statics {
+            doc(&mut out, "    ", c.doc.as_deref());
+            writeln!(out, "    pub unsafe static {}: {};", c.name, c.type_).unwrap();
+        }
+        out.push_str("}\n");
+    }
+
+    // no trailing blank line
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+
+    Ok(out)
+}
+
+fn doc(out: &mut String, indent: &str, d: Option<&str>) {
+    for l in d.into_iter().flat_map(str::lines) {
+        if l.is_empty() {
+            writeln!(out, "{indent}///").unwrap();
+        } else {
+            writeln!(out, "{indent}/// {l}").unwrap();
+        }
+    }
+}
+
+fn structure(out: &mut String, s: &Struct) {
+    doc(out, "", s.doc.as_deref());
+    out.push_str("#[repr(C)]\n");
+    if !s.derive.is_empty() {
+        writeln!(out, "#[derive({})]", s.derive.join(", ")).unwrap();
+    }
+    writeln!(out, "pub struct {} {{", s.name).unwrap();
+    for f in &s.fields {
+        doc(out, "    ", f.doc.as_deref());
+        writeln!(out, "    pub {}: {},", f.name, f.type_).unwrap();
+    }
+    out.push_str("}\n");
+
+    if s.encode {
+        writeln!(out, "unsafe impl Encode for {} {{", s.name).unwrap();
+        out.push_str("    fn encode() -> Encoding {\n");
+        out.push_str("        unsafe {\n");
+        out.push_str("            Encoding::from_str(&format!(\n");
+        writeln!(
+            out,
+            "                \"{{{{{}={}}}}}\",",
+            s.name,
+            "{}".repeat(s.fields.len())
+        )
+        .unwrap();
+        for (n, f) in s.fields.iter().enumerate() {
+            let sep = if n + 1 == s.fields.len() { "" } else { "," };
+            writeln!(out, "                {}::encode().as_str(){sep}", f.type_).unwrap();
+        }
+        out.push_str("            ))\n");
+        out.push_str("        }\n");
+        out.push_str("    }\n");
+        out.push_str("}\n");
+    }
+}
+
+fn core_object(out: &mut String, o: &CoreObject) -> Result<(), String> {
+    out.push_str("DefineCoreObject! {\n");
+    doc(out, "    ", o.doc.as_deref());
+    writeln!(out, "    pub {};", o.name).unwrap();
+    out.push_str("}\n");
+    let default_ref_doc = format!("A reference to a {} object.", o.name);
+    doc(out, "", Some(o.ref_doc.as_deref().unwrap_or(&default_ref_doc)));
+    let ptr = if o.mutable { "*mut" } else { "*const" };
+    writeln!(out, "pub type {0}Ref = {ptr} {0};", o.name).unwrap();
+
+    if o.methods.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "impl {} {{", o.name).unwrap();
+    for (n, m) in o.methods.iter().enumerate() {
+        if n > 0 {
+            out.push('\n');
+        }
+        core_method(out, m)?;
+    }
+    out.push_str("}\n");
+
+    Ok(())
+}
+
+fn core_method(out: &mut String, m: &CoreMethod) -> Result<(), String> {
+    let returns = match (m.kind, m.returns.as_deref()) {
+        (_, None) => String::new(),
+        (CallReturn::Value, Some(r)) => format!(" -> {r}"),
+        (CallReturn::Borrowed, Some(r)) => format!(" -> Result<&{r}, ()>"),
+        (CallReturn::Retained, Some(r)) => format!(" -> Result<CoreRetainedObject<{r}>, ()>"),
+    };
+    if m.returns.is_none() && m.kind != CallReturn::Value {
+        return Err(format!("{}: `kind = {:?}` requires `returns`", m.name, m.kind));
+    }
+
+    let mut call_args = Vec::new();
+    if m.receiver != Receiver::Class {
+        call_args.push(String::from("self"));
+    }
+    call_args.extend(m.args.iter().map(|a| {
+        if a.type_.starts_with("Option<&mut ") {
+            format!("opt_pointer_mut({})", a.name)
+        } else if a.type_.starts_with("Option<&") {
+            format!("opt_pointer({})", a.name)
+        } else {
+            a.name.clone()
+        }
+    }));
+    let call = format!("{}({})", m.function, call_args.join(", "));
+    let body = match m.kind {
+        CallReturn::Value => format!("unsafe {{ {call} }}"),
+        CallReturn::Borrowed => format!("unsafe {{ {call}.as_ref().ok_or(()) }}"),
+        CallReturn::Retained => format!("unsafe {{ CoreRetainedObject::retained({call}).ok_or(()) }}"),
+    };
+
+    doc(out, "    ", m.doc.as_deref());
+    out.push_str("    #[inline(always)]\n");
+    writeln!(
+        out,
+        "    pub fn {}({}){returns} {{",
+        m.name,
+        params(m.receiver, &m.args)
+    )
+    .unwrap();
+    writeln!(out, "        {body}").unwrap();
+    out.push_str("    }\n");
+
+    Ok(())
+}
+
+fn class(out: &mut String, c: &Class) -> Result<(), String> {
+    match &c.doc {
+        None => writeln!(
+            out,
+            "objc_ext::DefineObjcObjectWrapper!(pub {} : {});",
+            c.name, c.superclass
+        )
+        .unwrap(),
+        Some(d) => {
+            out.push_str("objc_ext::DefineObjcObjectWrapper! {\n");
+            doc(out, "    ", Some(d));
+            writeln!(out, "    pub {} : {};", c.name, c.superclass).unwrap();
+            out.push_str("}\n");
+        }
+    }
+
+    if c.methods.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "impl {} {{", c.name).unwrap();
+    for (n, m) in c.methods.iter().enumerate() {
+        if n > 0 {
+            out.push('\n');
+        }
+        method(out, &c.name, m)?;
+    }
+    out.push_str("}\n");
+
+    Ok(())
+}
+
+fn method(out: &mut String, class: &str, m: &Method) -> Result<(), String> {
+    let message = message(class, m)?;
+    let (returns, body) = match (m.kind, m.returns.as_deref()) {
+        (MessageReturn::Value, None) => (String::new(), vec![format!("let _: () = unsafe {{ {message} }};")]),
+        (_, None) => return Err(format!("{}: `kind = {:?}` requires `returns`", m.name, m.kind)),
+        (MessageReturn::Value, Some(r)) => (format!(" -> {r}"), vec![format!("unsafe {{ {message} }}")]),
+        (MessageReturn::Bool, Some(_)) => (
+            String::from(" -> bool"),
+            vec![
+                format!("let b: BOOL = unsafe {{ {message} }};"),
+                String::from("b == YES"),
+            ],
+        ),
+        (MessageReturn::Ref, Some(r)) => {
+            let (ty, conv) = match m.receiver {
+                Receiver::Ref => (format!("&{r}"), format!("(p as *const {r}).as_ref()")),
+                Receiver::Mut => (format!("&mut {r}"), format!("(p as *mut {r}).as_mut()")),
+                Receiver::Class => (format!("&'static {r}"), format!("(p as *const {r}).as_ref()")),
+            };
+            (
+                format!(" -> Option<{ty}>"),
+                vec![
+                    format!("let p: *mut Object = unsafe {{ {message} }};"),
+                    format!("unsafe {{ {conv} }}"),
+                ],
+            )
+        }
+        (MessageReturn::Retained, Some(r)) => (
+            format!(" -> Result<CocoaObject<{r}>, ()>"),
+            vec![format!(
+                "unsafe {{ CocoaObject::from_retained_id({message}).ok_or(()) }}"
+            )],
+        ),
+        (MessageReturn::RetainedMut, Some(r)) => (
+            format!(" -> Result<CocoaMutableObject<{r}>, ()>"),
+            vec![format!(
+                "unsafe {{ CocoaMutableObject::from_retained_id({message}).ok_or(()) }}"
+            )],
+        ),
+    };
+
+    doc(out, "    ", m.doc.as_deref());
+    out.push_str("    #[inline(always)]\n");
+    writeln!(
+        out,
+        "    pub fn {}({}){returns} {{",
+        m.name,
+        params(m.receiver, &m.args)
+    )
+    .unwrap();
+    for l in body {
+        writeln!(out, "        {l}").unwrap();
+    }
+    out.push_str("    }\n");
+
+    Ok(())
+}
+
+/// Builds the `msg_send![...]` expression, pairing selector parts with arguments in order.
+fn message(class: &str, m: &Method) -> Result<String, String> {
+    let receiver = match m.receiver {
+        Receiver::Class => format!("class!({class})"),
+        _ => String::from("self"),
+    };
+
+    if m.args.is_empty() {
+        if m.selector.contains(':') {
+            return Err(format!("{}: selector `{}` takes arguments", m.name, m.selector));
+        }
+
+        return Ok(format!("msg_send![{receiver}, {}]", m.selector));
+    }
+
+    let parts = m.selector.split_terminator(':').collect::<Vec<_>>();
+    if !m.selector.ends_with(':') || parts.len() != m.args.len() {
+        return Err(format!(
+            "{}: selector `{}` does not match {} argument(s)",
+            m.name,
+            m.selector,
+            m.args.len()
+        ));
+    }
+    let sends = parts
+        .iter()
+        .zip(&m.args)
+        .map(|(p, a)| {
+            if a.type_ == "bool" {
+                format!("{p}: if {0} {{ YES }} else {{ NO }}", a.name)
+            } else {
+                format!("{p}: {}", a.name)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(format!("msg_send![{receiver}, {}]", sends.join(" ")))
+}
+
+fn params(receiver: Receiver, args: &[Arg]) -> String {
+    let receiver = match receiver {
+        Receiver::Ref => Some("&self"),
+        Receiver::Mut => Some("&mut self"),
+        Receiver::Class => None,
+    };
+
+    receiver
+        .map(String::from)
+        .into_iter()
+        .chain(args.iter().map(|a| format!("{}: {}", a.name, a.type_)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,148 @@
+//! Binding generator for appkit-rs
+//!
+//! Reads an API description (TOML) and emits `msg_send!` wrappers, C function declarations,
+//! constants and structs in the style of the hand-written modules.
+//!
+//! ```text
+//! cargo xtask gen <description.toml> [-o <output.rs>]
+//! cargo xtask snapshots [--bless]
+//! ```
+
+mod description;
+mod emit;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage:
+    cargo xtask gen <description.toml> [-o <output.rs>]
+    cargo xtask snapshots [--bless]";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let r = match args.as_slice() {
+        ["gen", input] => generate(Path::new(input)).map(|s| print!("{s}")),
+        ["gen", input, "-o", output] => {
+            generate(Path::new(input)).and_then(|s| std::fs::write(output, s).map_err(|e| format!("{output}: {e}")))
+        }
+        ["snapshots"] => snapshots(false),
+        ["snapshots", "--bless"] => snapshots(true),
+        _ => Err(String::from(USAGE)),
+    };
+
+    match r {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn generate(input: &Path) -> Result<String, String> {
+    let src = std::fs::read_to_string(input).map_err(|e| format!("{}: {e}", input.display()))?;
+    let m = toml::from_str::<description::Module>(&src).map_err(|e| format!("{}: {e}", input.display()))?;
+
+    let src = emit::module(&m).map_err(|e| format!("{}: {e}", input.display()))?;
+
+    rustfmt(&src)
+}
+
+/// Formats the generated source with the workspace `rustfmt.toml`.
+fn rustfmt(src: &str) -> Result<String, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let config = Path::new(env!("CARGO_MANIFEST_DIR")).join("../rustfmt.toml");
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021", "--emit", "stdout", "--config-path"])
+        .arg(&config)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run rustfmt: {e}"))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(src.as_bytes())
+        .map_err(|e| format!("failed to run rustfmt: {e}"))?;
+    let out = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to run rustfmt: {e}"))?;
+
+    if !out.status.success() {
+        return Err(format!("rustfmt failed:\n{}", String::from_utf8_lossy(&out.stderr)));
+    }
+    String::from_utf8(out.stdout).map_err(|e| format!("rustfmt produced invalid UTF-8: {e}"))
+}
+
+/// Regenerates every `snapshots/*.toml` and compares the result with the `.rs` file next to it.
+fn snapshots(bless: bool) -> Result<(), String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("snapshots");
+    let mut inputs = std::fs::read_dir(&dir)
+        .map_err(|e| format!("{}: {e}", dir.display()))?
+        .filter_map(|e| Some(e.ok()?.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == "toml"))
+        .collect::<Vec<PathBuf>>();
+    inputs.sort();
+
+    let mut failures = 0;
+    for input in &inputs {
+        let expected_path = input.with_extension("rs");
+        let generated = generate(input)?;
+
+        if bless {
+            std::fs::write(&expected_path, &generated).map_err(|e| format!("{}: {e}", expected_path.display()))?;
+            println!("blessed {}", expected_path.display());
+            continue;
+        }
+
+        let expected = std::fs::read_to_string(&expected_path).unwrap_or_default();
+        if expected == generated {
+            println!("ok      {}", input.display());
+        } else {
+            failures += 1;
+            println!("FAILED  {}", input.display());
+            print_first_difference(&expected, &generated);
+        }
+    }
+
+    if failures > 0 {
+        Err(format!(
+            "{failures} snapshot(s) differ; run `cargo xtask snapshots --bless` to accept the new output"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn print_first_difference(expected: &str, generated: &str) {
+    let (mut e, mut g) = (expected.lines(), generated.lines());
+    for line in 1.. {
+        match (e.next(), g.next()) {
+            (None, None) => break,
+            (l, r) if l == r => continue,
+            (l, r) => {
+                println!("        line {line}:");
+                println!("          - {}", l.unwrap_or("<end of file>"));
+                println!("          + {}", r.unwrap_or("<end of file>"));
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// Keeps the checked-in snapshots in sync with the emitter (same as `cargo xtask snapshots`).
+    #[test]
+    fn snapshots_match_emitted_code() {
+        if let Err(e) = super::snapshots(false) {
+            panic!("{e}");
+        }
+    }
+}